@@ -0,0 +1,259 @@
+use crate::fiat_shamir::Transcript;
+use crate::field::Field;
+use crate::finite_field::FiniteFieldElement;
+use crate::hashing::Hasher;
+
+/// Boundary constraint: the trace value in `column` at `row` must equal `value`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundaryConstraint<F: Field = FiniteFieldElement> {
+    pub column: usize,
+    pub row: usize,
+    pub value: F,
+}
+
+/// Public inputs: the claim a proof is about, known to both prover and verifier.
+///
+/// Carried next to the proof and absorbed into the Fiat–Shamir transcript, so
+/// every challenge depends on what is being proven.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicInputs<F: Field = FiniteFieldElement> {
+    /// Number of rows (computational steps) in the trace
+    pub trace_length: usize,
+    /// Claimed trace values
+    pub boundary_constraints: Vec<BoundaryConstraint<F>>,
+}
+
+impl<F: Field> PublicInputs<F> {
+    /// Append the public inputs to a Fiat–Shamir transcript
+    pub fn absorb_into<H: Hasher>(&self, transcript: &mut Transcript<H>) {
+        transcript.append_u64(b"trace_length", self.trace_length as u64);
+        transcript.append_u64(
+            b"boundary_constraints",
+            self.boundary_constraints.len() as u64,
+        );
+        for c in &self.boundary_constraints {
+            transcript.append_u64(b"boundary_column", c.column as u64);
+            transcript.append_u64(b"boundary_row", c.row as u64);
+            transcript.append_message(b"boundary_value", &c.value.to_canonical_bytes());
+        }
+    }
+}
+
+/// Algebraic Intermediate Representation (AIR) of a computation.
+///
+/// Describes what a valid execution trace looks like: its shape, the transition
+/// constraints every pair of consecutive rows must satisfy and the boundary
+/// constraints pinning individual cells. The prover and verifier only talk to
+/// the computation through this trait.
+pub trait Air {
+    /// The field the trace and the constraints live in
+    type Field: Field;
+
+    /// Number of columns (state variables) in the trace
+    fn trace_width(&self) -> usize;
+
+    /// Number of rows (computational steps) in the trace
+    fn trace_length(&self) -> usize;
+
+    /// Degree of each transition constraint, in the same order as `evaluate_transition` returns them
+    fn transition_constraint_degrees(&self) -> Vec<usize>;
+
+    /// Evaluate all transition constraints on two consecutive rows.
+    /// Every returned value must be zero for a valid trace.
+    fn evaluate_transition(
+        &self,
+        current: &[Self::Field],
+        next: &[Self::Field],
+    ) -> Vec<Self::Field>;
+
+    /// Constraints of the form "column c at row r equals v"
+    fn boundary_constraints(&self) -> Vec<BoundaryConstraint<Self::Field>>;
+
+    /// Number of transition constraints
+    fn num_transition_constraints(&self) -> usize {
+        self.transition_constraint_degrees().len()
+    }
+
+    /// The claim proven about this computation
+    fn public_inputs(&self) -> PublicInputs<Self::Field> {
+        PublicInputs {
+            trace_length: self.trace_length(),
+            boundary_constraints: self.boundary_constraints(),
+        }
+    }
+
+    /// Highest transition constraint degree
+    fn max_constraint_degree(&self) -> usize {
+        self.transition_constraint_degrees()
+            .into_iter()
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Random linear combination Σ_j α_j · residual_j of transition constraint residuals
+pub fn combine_constraints<F: Field>(residuals: &[F], alphas: &[F]) -> F {
+    let mut combined = F::ZERO;
+    for (residual, alpha) in residuals.iter().zip(alphas.iter()) {
+        combined = combined.add(residual.multiply(*alpha));
+    }
+    combined
+}
+
+/// Example: Fibonacci AIR matching `trace::fibonacci`
+pub mod fibonacci {
+    use super::{Air, BoundaryConstraint};
+    use crate::field::Field;
+    use crate::finite_field::FiniteFieldElement;
+    use crate::trace::Trace;
+
+    /// Columns: [F(n-2), F(n-1), F(n)]
+    ///
+    /// Transition constraints:
+    /// - next[0] = current[1] (window shifts left)
+    /// - next[1] = current[2] (window shifts left)
+    /// - next[2] = current[1] + current[2] (Fibonacci rule)
+    ///
+    /// Public inputs: the first value, the claimed final value and the number of steps.
+    pub struct FibonacciAir<F: Field = FiniteFieldElement> {
+        pub num_steps: usize,
+        pub first_value: F,
+        pub result: F,
+    }
+
+    impl<F: Field> FibonacciAir<F> {
+        pub fn new(num_steps: usize, first_value: F, result: F) -> Self {
+            FibonacciAir {
+                num_steps,
+                first_value,
+                result,
+            }
+        }
+
+        /// Read the AIR parameters from an existing Fibonacci trace
+        pub fn from_trace(trace: &Trace<F>) -> Self {
+            let last_row = trace.num_rows() - 1;
+            FibonacciAir::new(
+                trace.num_rows(),
+                trace.get(0, 2).unwrap(),
+                trace.get(last_row, 2).unwrap(),
+            )
+        }
+    }
+
+    impl<F: Field> Air for FibonacciAir<F> {
+        type Field = F;
+
+        fn trace_width(&self) -> usize {
+            3
+        }
+
+        fn trace_length(&self) -> usize {
+            self.num_steps
+        }
+
+        fn transition_constraint_degrees(&self) -> Vec<usize> {
+            vec![1, 1, 1]
+        }
+
+        fn evaluate_transition(&self, current: &[F], next: &[F]) -> Vec<F> {
+            vec![
+                next[0].subtract(current[1]),
+                next[1].subtract(current[2]),
+                next[2].subtract(current[1]).subtract(current[2]),
+            ]
+        }
+
+        fn boundary_constraints(&self) -> Vec<BoundaryConstraint<F>> {
+            // First row is [F(-2), F(-1), F(0)] = [a, 0, a], last row ends in the result
+            vec![
+                BoundaryConstraint {
+                    column: 0,
+                    row: 0,
+                    value: self.first_value,
+                },
+                BoundaryConstraint {
+                    column: 1,
+                    row: 0,
+                    value: F::ZERO,
+                },
+                BoundaryConstraint {
+                    column: 2,
+                    row: 0,
+                    value: self.first_value,
+                },
+                BoundaryConstraint {
+                    column: 2,
+                    row: self.num_steps - 1,
+                    value: self.result,
+                },
+            ]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fibonacci::FibonacciAir;
+    use super::*;
+    use crate::trace::{fibonacci, Trace};
+
+    type F = FiniteFieldElement;
+
+    /// Evaluate every transition constraint over the whole trace
+    fn all_transitions_zero(air: &FibonacciAir, trace: &Trace) -> bool {
+        trace.trace.windows(2).all(|pair| {
+            air.evaluate_transition(&pair[0], &pair[1])
+                .iter()
+                .all(|v| v.is_zero())
+        })
+    }
+
+    #[test]
+    fn fibonacci_trace_satisfies_air() {
+        let trace = fibonacci::generate_fibonacci_trace(8, F::ONE, F::ONE);
+        let air = FibonacciAir::from_trace(&trace);
+
+        assert_eq!(air.trace_width(), trace.num_columns());
+        assert_eq!(air.trace_length(), trace.num_rows());
+        assert_eq!(air.num_transition_constraints(), 3);
+        assert_eq!(air.max_constraint_degree(), 1);
+        assert!(all_transitions_zero(&air, &trace));
+
+        for c in air.boundary_constraints() {
+            assert_eq!(trace.get(c.row, c.column), Some(c.value));
+        }
+    }
+
+    #[test]
+    fn fibonacci_public_inputs() {
+        let trace = fibonacci::generate_fibonacci_trace(8, F::ONE, F::ONE);
+        let air = FibonacciAir::from_trace(&trace);
+        assert_eq!(air.result, F::new(21));
+
+        let public_inputs = air.public_inputs();
+        assert_eq!(public_inputs.trace_length, 8);
+        assert!(public_inputs
+            .boundary_constraints
+            .contains(&BoundaryConstraint {
+                column: 2,
+                row: 7,
+                value: F::new(21)
+            }));
+
+        // A different claim gives different public inputs
+        assert_ne!(
+            public_inputs,
+            FibonacciAir::new(8, F::ONE, F::new(22)).public_inputs()
+        );
+    }
+
+    #[test]
+    fn tampered_trace_violates_air() {
+        let mut trace = fibonacci::generate_fibonacci_trace(8, F::ONE, F::ONE);
+        trace.trace[5][2] = trace.trace[5][2].add(F::ONE);
+        let air = FibonacciAir::from_trace(&trace);
+
+        assert!(!all_transitions_zero(&air, &trace));
+    }
+}
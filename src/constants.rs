@@ -1,2 +1,7 @@
-pub const EXTENSION_FACTOR: usize = 4;
+pub const DEFAULT_BLOWUP_FACTOR: usize = 4;
+pub const DEFAULT_NUM_QUERIES: usize = 5;
+pub const DEFAULT_FRI_FOLDING_FACTOR: usize = 2;
+pub const DEFAULT_FRI_MAX_REMAINDER_DEGREE: usize = 0;
+pub const DEFAULT_GRINDING_BITS: u32 = 8;
 pub const DEFAULT_FIELD_SIZE: i128 = 3 * 2_i128.pow(30) + 1;
+pub const TARGET_SECURITY_BITS: u32 = 100;
@@ -1,21 +1,87 @@
-use crate::finite_field::{FiniteField, FiniteFieldElement};
+use crate::field::Field;
+use crate::finite_field::FiniteFieldElement;
 
-/// Minimal, naive evaluation domain: points are [0, 1, ..., n-1] in the field.
+/// Minimal, naive evaluation domain: a list of points in the field. Either
+/// [0, 1, ..., n-1], arbitrary points, or a coset [h, h·ω, ..., h·ω^(n-1)] of the
+/// subgroup generated by a primitive n-th root of unity ω (h = 1 for the subgroup).
 #[derive(Debug, Clone)]
-pub struct EvaluationDomain {
-    pub field: FiniteField,
-    pub points: Vec<FiniteFieldElement>,
+pub struct EvaluationDomain<F: Field = FiniteFieldElement> {
+    pub points: Vec<F>,
+    /// (ω, h) for subgroup and coset domains
+    coset: Option<(F, F)>,
 }
 
-impl EvaluationDomain {
-    /// Create a domain with points 0..n-1 over `field`.
-    pub fn new_linear(field: FiniteField, n: usize) -> Self {
+impl<F: Field> EvaluationDomain<F> {
+    /// Create a domain with points 0..n-1.
+    pub fn new_linear(n: usize) -> Self {
         assert!(n > 0);
         let mut points = Vec::with_capacity(n);
         for i in 0..n {
-            points.push(FiniteFieldElement::new_fielded(i as i128, field));
+            points.push(F::from_u64(i as u64));
+        }
+        EvaluationDomain::from_points(points)
+    }
+
+    /// Create a domain from arbitrary distinct points.
+    pub fn from_points(points: Vec<F>) -> Self {
+        EvaluationDomain {
+            points,
+            coset: None,
+        }
+    }
+
+    /// Create the subgroup [1, ω, ω^2, ..., ω^(n-1)] for a primitive n-th root of unity ω.
+    pub fn new_subgroup(n: usize) -> Self {
+        EvaluationDomain::new_coset(n, F::ONE)
+    }
+
+    /// Create the coset [h, h·ω, ..., h·ω^(n-1)] of the subgroup of order n, h = `offset`.
+    /// Panics if the field has no subgroup of order n.
+    pub fn new_coset(n: usize, offset: F) -> Self {
+        assert!(!offset.is_zero(), "offset must be non-zero");
+        let generator = F::primitive_root_of_unity(n).unwrap_or_else(|e| panic!("{}", e));
+        EvaluationDomain::from_generator(generator, offset, n)
+    }
+
+    fn from_generator(generator: F, offset: F, n: usize) -> Self {
+        let mut points = Vec::with_capacity(n);
+        let mut current = offset;
+        for _ in 0..n {
+            points.push(current);
+            current = current.multiply(generator);
+        }
+        EvaluationDomain {
+            points,
+            coset: Some((generator, offset)),
+        }
+    }
+
+    /// Generator ω of the underlying subgroup, if this is a subgroup or coset domain.
+    pub fn generator(&self) -> Option<F> {
+        self.coset.map(|(generator, _)| generator)
+    }
+
+    /// Coset offset h (1 for a subgroup), if this is a subgroup or coset domain.
+    pub fn offset(&self) -> Option<F> {
+        self.coset.map(|(_, offset)| offset)
+    }
+
+    /// Domain of the next FRI layer: the squares of the first half of the points.
+    /// For a subgroup or coset of even size, points[i + n/2] = -points[i],
+    /// so this is the image of the whole domain under x -> x^2.
+    pub fn square(&self) -> Self {
+        let half = self.size() / 2;
+        match self.coset {
+            Some((generator, offset)) => EvaluationDomain::from_generator(
+                generator.multiply(generator),
+                offset.multiply(offset),
+                half,
+            ),
+            None => {
+                let points = self.points[..half].iter().map(|x| x.multiply(*x)).collect();
+                EvaluationDomain::from_points(points)
+            }
         }
-        EvaluationDomain { field, points }
     }
 
     /// Number of points in the domain.
@@ -24,17 +90,94 @@ impl EvaluationDomain {
     }
 
     /// i-th point in the domain.
-    pub fn element(&self, i: usize) -> FiniteFieldElement {
+    pub fn element(&self, i: usize) -> F {
         self.points[i]
     }
 
     /// Vanishing polynomial Z_H(x) = ∏(x - a_i) over all domain points a_i.
-    /// This is O(n) per evaluation; fine for tiny, educational setups.
-    pub fn evaluate_vanishing(&self, x: FiniteFieldElement) -> FiniteFieldElement {
-        let mut acc = FiniteFieldElement::new_fielded(1, self.field);
+    /// For a coset of the subgroup of order n this is x^n - h^n; other domains
+    /// take O(n) per evaluation, fine for tiny, educational setups.
+    pub fn evaluate_vanishing(&self, x: F) -> F {
+        if let Some((_, offset)) = self.coset {
+            let n = self.size() as u64;
+            return x.pow(n).subtract(offset.pow(n));
+        }
+        let mut acc = F::ONE;
         for a in &self.points {
             acc = acc.multiply(x.subtract(*a));
         }
         acc
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type F = FiniteFieldElement;
+
+    #[test]
+    #[should_panic]
+    fn subgroup_needs_order_dividing_p_minus_one() {
+        let _ = EvaluationDomain::<F>::new_subgroup(5);
+    }
+
+    #[test]
+    fn subgroup_is_generated_by_primitive_root() {
+        let domain = EvaluationDomain::<F>::new_subgroup(16);
+        let generator = domain.generator().unwrap();
+
+        assert_eq!(domain.size(), 16);
+        assert_eq!(domain.offset(), Some(F::ONE));
+        assert_eq!(domain.element(0), F::ONE);
+        assert_eq!(domain.element(1), generator);
+        assert_eq!(generator.pow(16), F::ONE);
+        assert_ne!(generator.pow(8), F::ONE);
+        // x -> -x pairs up the two halves
+        for i in 0..8 {
+            assert_eq!(domain.element(i + 8), domain.element(i).negate());
+        }
+    }
+
+    #[test]
+    fn coset_vanishing_is_closed_form() {
+        let offset = F::multiplicative_generator();
+        let coset = EvaluationDomain::new_coset(8, offset);
+        let naive = EvaluationDomain::from_points(coset.points.clone());
+
+        assert_eq!(coset.offset(), Some(offset));
+        for point in &coset.points {
+            assert!(coset.evaluate_vanishing(*point).is_zero());
+        }
+        for x in [0, 1, 2, 12345] {
+            let x = F::new(x);
+            assert_eq!(coset.evaluate_vanishing(x), naive.evaluate_vanishing(x));
+            assert_eq!(
+                coset.evaluate_vanishing(x),
+                x.pow(8).subtract(offset.pow(8))
+            );
+        }
+    }
+
+    #[test]
+    fn square_of_coset_is_coset() {
+        let coset = EvaluationDomain::new_coset(16, F::multiplicative_generator());
+        let squared = coset.square();
+
+        assert_eq!(squared.size(), 8);
+        assert_eq!(squared.generator(), Some(coset.generator().unwrap().pow(2)));
+        for i in 0..8 {
+            assert_eq!(squared.element(i), coset.element(i).pow(2));
+            assert_eq!(squared.element(i), coset.element(i + 8).pow(2));
+        }
+    }
+
+    #[test]
+    fn domains_in_a_small_field() {
+        type F17 = FiniteFieldElement<17>;
+        let domain = EvaluationDomain::<F17>::new_subgroup(16);
+        assert_eq!(domain.generator(), Some(F17::from_i128(3)));
+        assert!(domain.evaluate_vanishing(F17::from_i128(5)).is_zero());
+        assert_eq!(EvaluationDomain::<F17>::new_linear(3).element(2).value, 2);
+    }
+}
@@ -0,0 +1,491 @@
+use core::fmt;
+
+use crate::field::{Field, FieldError};
+use crate::finite_field::{is_non_residue, smallest_non_residue, FiniteFieldElement};
+use crate::goldilocks::Goldilocks;
+use crate::montgomery::MontgomeryElement;
+
+/// A base field with a chosen W for the degree D extension F[x] / (x^D - W)
+pub trait BinomialExtension<const D: usize>: Field {
+    /// W in the modulus x^D - W; must not be a D-th power for x^D - W to be
+    /// irreducible
+    const NONRESIDUE: Self;
+    /// W as its canonical value, so the modulus can be checked at compile time
+    const NONRESIDUE_VALUE: u64;
+}
+
+/// Smallest non-residue of the right degree; a modulus that cannot be made
+/// irreducible this way gets W = 0 and does not compile
+impl<const P: i128, const D: usize> BinomialExtension<D> for FiniteFieldElement<P> {
+    const NONRESIDUE: Self =
+        Self::from_value(<Self as BinomialExtension<D>>::NONRESIDUE_VALUE as i128);
+    const NONRESIDUE_VALUE: u64 = smallest_non_residue(P as u64, D as u64);
+}
+
+impl<const P: u32, const D: usize> BinomialExtension<D> for MontgomeryElement<P> {
+    const NONRESIDUE: Self = Self::from_value(<Self as BinomialExtension<D>>::NONRESIDUE_VALUE);
+    const NONRESIDUE_VALUE: u64 = smallest_non_residue(P as u64, D as u64);
+}
+
+/// 7 generates the multiplicative group, so it is no square and no cube
+impl<const D: usize> BinomialExtension<D> for Goldilocks {
+    const NONRESIDUE: Self = Goldilocks::new(<Self as BinomialExtension<D>>::NONRESIDUE_VALUE);
+    const NONRESIDUE_VALUE: u64 = 7;
+}
+
+/// Why an extension cannot be built
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtensionError {
+    /// Only quadratic and cubic extensions are supported
+    UnsupportedDegree(usize),
+    /// x^D - W has a factor over the base field, so the quotient is not a field
+    ReducibleModulus { degree: usize, characteristic: u64 },
+}
+
+impl fmt::Display for ExtensionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtensionError::UnsupportedDegree(d) => {
+                write!(f, "extension degree {} is not supported (only 2 and 3)", d)
+            }
+            ExtensionError::ReducibleModulus {
+                degree,
+                characteristic,
+            } => write!(
+                f,
+                "x^{} - W is reducible over the field of characteristic {}",
+                degree, characteristic
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ExtensionError {}
+
+/// Element a_0 + a_1·x + ... + a_{D-1}·x^(D-1) of the degree D extension
+/// F[x] / (x^D - W) of the base field F. Challenges drawn here carry D times the
+/// bits of the base field. Domains and roots of unity stay in the base field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtensionField<const D: usize, F: BinomialExtension<D> = FiniteFieldElement> {
+    coefficients: [F; D],
+}
+
+impl<const D: usize, F: BinomialExtension<D>> ExtensionField<D, F> {
+    /// Whether x^D - W is irreducible: for prime D that holds if and only if
+    /// D divides p - 1 and W is not a D-th power, i.e. W^((p - 1) / D) != 1.
+    /// Evaluated once per type, at compile time.
+    const MODULUS: Result<(), ExtensionError> = if D != 2 && D != 3 {
+        Err(ExtensionError::UnsupportedDegree(D))
+    } else if !is_non_residue(F::NONRESIDUE_VALUE, F::CHARACTERISTIC, D as u64) {
+        Err(ExtensionError::ReducibleModulus {
+            degree: D,
+            characteristic: F::CHARACTERISTIC,
+        })
+    } else {
+        Ok(())
+    };
+
+    /// Referenced by every constructor, so an extension with a reducible modulus
+    /// does not compile
+    const MODULUS_IS_IRREDUCIBLE: () = assert!(
+        Self::MODULUS.is_ok(),
+        "x^D - W must be irreducible, with D = 2 or 3"
+    );
+
+    /// Check that x^D - W is irreducible, without requiring it
+    pub const fn check_modulus() -> Result<(), ExtensionError> {
+        Self::MODULUS
+    }
+
+    /// Element from its coefficients, lowest first. Does not compile if the
+    /// modulus of this extension is reducible:
+    ///
+    /// ```compile_fail
+    /// # use stark_from_zero::extension_field::ExtensionField;
+    /// # use stark_from_zero::finite_field::FiniteFieldElement;
+    /// // 3 does not divide 16, so every element of F_17 is a cube
+    /// type F17 = FiniteFieldElement<17>;
+    /// ExtensionField::<3, F17>::new([F17::from_value(1); 3]);
+    /// ```
+    pub const fn new(coefficients: [F; D]) -> Self {
+        let () = Self::MODULUS_IS_IRREDUCIBLE;
+        ExtensionField { coefficients }
+    }
+
+    /// The base field element `value`, embedded as a constant
+    pub fn from_base(value: F) -> Self {
+        let mut coefficients = [F::ZERO; D];
+        coefficients[0] = value;
+        Self::new(coefficients)
+    }
+
+    /// Coefficients a_0, ..., a_{D-1}, lowest first
+    pub fn coefficients(&self) -> [F; D] {
+        self.coefficients
+    }
+
+    /// Multiply every coefficient by a base field element
+    pub fn scale(&self, factor: F) -> Self {
+        ExtensionField {
+            coefficients: self.coefficients.map(|c| c.multiply(factor)),
+        }
+    }
+
+    /// The Frobenius map a -> a^p. Since x^p = W^((p - 1) / D) · x, it multiplies
+    /// coefficient i by W^(i · (p - 1) / D).
+    pub fn frobenius(&self) -> Self {
+        let gamma = F::NONRESIDUE.pow((F::CHARACTERISTIC - 1) / D as u64);
+        let mut twist = F::ONE;
+        let mut coefficients = self.coefficients;
+        for c in coefficients.iter_mut() {
+            *c = c.multiply(twist);
+            twist = twist.multiply(gamma);
+        }
+        ExtensionField { coefficients }
+    }
+
+    /// N(a) = a · a^p · ... · a^(p^(D-1)), which lies in the base field
+    pub fn norm(&self) -> F {
+        let mut product = *self;
+        let mut conjugate = *self;
+        for _ in 1..D {
+            conjugate = conjugate.frobenius();
+            product = product.multiply(conjugate);
+        }
+        product.coefficients[0]
+    }
+}
+
+/// Zero
+impl<const D: usize, F: BinomialExtension<D>> Default for ExtensionField<D, F> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+/// `a_0 + a_1·x + a_2·x^2`
+impl<const D: usize, F: BinomialExtension<D>> fmt::Display for ExtensionField<D, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, c) in self.coefficients.iter().enumerate() {
+            match i {
+                0 => write!(f, "{}", c)?,
+                1 => write!(f, " + {}·x", c)?,
+                _ => write!(f, " + {}·x^{}", c, i)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<const D: usize, F: BinomialExtension<D>> Field for ExtensionField<D, F> {
+    const ZERO: Self = Self::new([F::ZERO; D]);
+    const ONE: Self = {
+        let mut coefficients = [F::ZERO; D];
+        coefficients[0] = F::ONE;
+        Self::new(coefficients)
+    };
+    const CHARACTERISTIC: u64 = F::CHARACTERISTIC;
+    /// Only the power-of-two subgroups of the base field are used
+    const TWO_ADICITY: u32 = F::TWO_ADICITY;
+    const ENCODED_SIZE: usize = D * F::ENCODED_SIZE;
+    const RANDOM_BYTES: usize = D * F::RANDOM_BYTES;
+
+    fn from_u64(value: u64) -> Self {
+        Self::from_base(F::from_u64(value))
+    }
+
+    fn add(&self, other: Self) -> Self {
+        let mut coefficients = self.coefficients;
+        for (c, o) in coefficients.iter_mut().zip(other.coefficients) {
+            *c = c.add(o);
+        }
+        ExtensionField { coefficients }
+    }
+
+    fn subtract(&self, other: Self) -> Self {
+        let mut coefficients = self.coefficients;
+        for (c, o) in coefficients.iter_mut().zip(other.coefficients) {
+            *c = c.subtract(o);
+        }
+        ExtensionField { coefficients }
+    }
+
+    fn multiply(&self, other: Self) -> Self {
+        // Schoolbook product, then x^(D + k) = W · x^k folds the high half down
+        let mut coefficients = [F::ZERO; D];
+        for (i, a) in self.coefficients.iter().enumerate() {
+            for (j, b) in other.coefficients.iter().enumerate() {
+                let product = a.multiply(*b);
+                if i + j < D {
+                    coefficients[i + j] = coefficients[i + j].add(product);
+                } else {
+                    let folded = product.multiply(F::NONRESIDUE);
+                    coefficients[i + j - D] = coefficients[i + j - D].add(folded);
+                }
+            }
+        }
+        ExtensionField { coefficients }
+    }
+
+    fn negate(&self) -> Self {
+        ExtensionField {
+            coefficients: self.coefficients.map(|c| c.negate()),
+        }
+    }
+
+    /// a^-1 = (a^p · ... · a^(p^(D-1))) / N(a), with one inversion in the base field
+    fn inverse(&self) -> Self {
+        let mut conjugates = Self::ONE;
+        let mut conjugate = *self;
+        for _ in 1..D {
+            conjugate = conjugate.frobenius();
+            conjugates = conjugates.multiply(conjugate);
+        }
+        let norm = self.multiply(conjugates).coefficients[0];
+        conjugates.scale(norm.inverse())
+    }
+
+    /// The generator of the base field, which domains and cosets are built from
+    fn multiplicative_generator() -> Self {
+        Self::from_base(F::multiplicative_generator())
+    }
+
+    /// Roots of unity of the base field
+    fn primitive_root_of_unity(n: usize) -> Result<Self, FieldError> {
+        F::primitive_root_of_unity(n).map(Self::from_base)
+    }
+
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        self.coefficients
+            .iter()
+            .flat_map(|c| c.to_canonical_bytes())
+            .collect()
+    }
+
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::ENCODED_SIZE {
+            return None;
+        }
+        let mut coefficients = [F::ZERO; D];
+        for (c, chunk) in coefficients
+            .iter_mut()
+            .zip(bytes.chunks_exact(F::ENCODED_SIZE))
+        {
+            *c = F::from_canonical_bytes(chunk)?;
+        }
+        Some(Self::new(coefficients))
+    }
+
+    /// Each coefficient from its own `F::RANDOM_BYTES` bytes
+    fn from_random_bytes(bytes: &[u8]) -> Self {
+        let mut coefficients = [F::ZERO; D];
+        for (c, chunk) in coefficients.iter_mut().zip(bytes.chunks(F::RANDOM_BYTES)) {
+            *c = F::from_random_bytes(chunk);
+        }
+        Self::new(coefficients)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::DEFAULT_FIELD_SIZE;
+    use crate::fiat_shamir::Transcript;
+    use crate::polynomial::polynomial::Polynomial;
+
+    type F = FiniteFieldElement;
+    type E2 = ExtensionField<2>;
+    type E3 = ExtensionField<3>;
+    type G2 = ExtensionField<2, Goldilocks>;
+    type G3 = ExtensionField<3, Goldilocks>;
+
+    fn e2(a: i128, b: i128) -> E2 {
+        E2::new([F::new(a), F::new(b)])
+    }
+
+    fn e3(a: i128, b: i128, c: i128) -> E3 {
+        E3::new([F::new(a), F::new(b), F::new(c)])
+    }
+
+    /// A spread of elements with every coefficient non-trivial
+    fn samples<const D: usize, B: BinomialExtension<D>>() -> Vec<ExtensionField<D, B>> {
+        let mut x: u64 = 0x2545_f491_4f6c_dd1d;
+        (0..16)
+            .map(|_| {
+                let mut coefficients = [B::ZERO; D];
+                for c in coefficients.iter_mut() {
+                    x = x
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    *c = B::from_u64(x >> 1);
+                }
+                ExtensionField::new(coefficients)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn moduli_are_irreducible() {
+        // 5 is the smallest non-square mod 3 · 2^30 + 1, 2 is not even a square
+        assert_eq!(<F as BinomialExtension<2>>::NONRESIDUE, F::new(5));
+        assert!(E2::check_modulus().is_ok());
+        assert!(E3::check_modulus().is_ok());
+        assert!(G2::check_modulus().is_ok());
+        assert!(G3::check_modulus().is_ok());
+        assert!(ExtensionField::<2, MontgomeryElement>::check_modulus().is_ok());
+
+        // The value the compile-time check uses is the W the arithmetic uses
+        fn same_w<const D: usize, B: BinomialExtension<D>>() {
+            assert_eq!(B::NONRESIDUE, B::from_u64(B::NONRESIDUE_VALUE));
+        }
+        same_w::<2, F>();
+        same_w::<3, F>();
+        same_w::<3, Goldilocks>();
+        same_w::<2, MontgomeryElement>();
+    }
+
+    #[test]
+    fn reducible_moduli_are_refused() {
+        // 3 does not divide 16, so every element of F_17 is a cube
+        assert_eq!(
+            ExtensionField::<3, FiniteFieldElement<17>>::check_modulus(),
+            Err(ExtensionError::ReducibleModulus {
+                degree: 3,
+                characteristic: 17
+            })
+        );
+        assert_eq!(
+            ExtensionField::<4>::check_modulus(),
+            Err(ExtensionError::UnsupportedDegree(4))
+        );
+    }
+
+    #[test]
+    fn quadratic_arithmetic() {
+        // (1 + 2x)(3 + 4x) = 3 + 10x + 8x^2, with x^2 = 5
+        assert_eq!(e2(1, 2).multiply(e2(3, 4)), e2(43, 10));
+        assert_eq!(e2(1, 2).add(e2(3, 4)), e2(4, 6));
+        assert_eq!(e2(1, 2).subtract(e2(3, 4)), e2(-2, -2));
+        assert_eq!(e2(1, 2).negate(), e2(-1, -2));
+        // x · x = W
+        assert_eq!(e2(0, 1).multiply(e2(0, 1)), E2::from_base(F::new(5)));
+        assert_eq!(e2(7, 0), E2::from_u64(7));
+    }
+
+    #[test]
+    fn cubic_arithmetic() {
+        let w = <F as BinomialExtension<3>>::NONRESIDUE;
+        let x = e3(0, 1, 0);
+        assert_eq!(x.pow(3), E3::from_base(w));
+        // (1 + x)(1 + x^2) = 1 + x + x^2 + W
+        assert_eq!(
+            e3(1, 1, 0).multiply(e3(1, 0, 1)),
+            E3::new([F::ONE.add(w), F::ONE, F::ONE])
+        );
+    }
+
+    fn field_axioms<const D: usize, B: BinomialExtension<D>>() {
+        let values = samples::<D, B>();
+        let one = ExtensionField::<D, B>::ONE;
+        for &a in &values {
+            assert_eq!(a.multiply(a.inverse()), one);
+            assert_eq!(a.add(a.negate()), ExtensionField::ZERO);
+            for &b in &values {
+                assert_eq!(a.multiply(b), b.multiply(a));
+                assert_eq!(a.multiply(b).multiply(b.inverse()), a);
+                let c = values[0];
+                assert_eq!(
+                    a.multiply(b.add(c)),
+                    a.multiply(b).add(a.multiply(c)),
+                    "distributivity"
+                );
+            }
+        }
+        assert_eq!(ExtensionField::<D, B>::ZERO.inverse(), ExtensionField::ZERO);
+    }
+
+    #[test]
+    fn field_axioms_hold() {
+        field_axioms::<2, F>();
+        field_axioms::<3, F>();
+        field_axioms::<2, Goldilocks>();
+        field_axioms::<3, Goldilocks>();
+    }
+
+    #[test]
+    fn frobenius_is_the_p_th_power() {
+        for a in samples::<2, F>() {
+            assert_eq!(a.frobenius(), a.pow(DEFAULT_FIELD_SIZE as u64));
+            // Order D, and fixes exactly the base field
+            assert_eq!(a.frobenius().frobenius(), a);
+            assert_eq!(E2::from_base(a.norm()).frobenius(), E2::from_base(a.norm()));
+        }
+        for a in samples::<3, Goldilocks>() {
+            assert_eq!(a.frobenius(), a.pow(Goldilocks::CHARACTERISTIC));
+            assert_eq!(a.frobenius().frobenius().frobenius(), a);
+        }
+        let base = E3::from_u64(12345);
+        assert_eq!(base.frobenius(), base);
+    }
+
+    #[test]
+    fn norm_is_multiplicative() {
+        let values = samples::<3, F>();
+        for &a in &values {
+            for &b in &values {
+                assert_eq!(a.multiply(b).norm(), a.norm().multiply(b.norm()));
+            }
+        }
+    }
+
+    #[test]
+    fn canonical_bytes() {
+        for a in samples::<3, Goldilocks>() {
+            let bytes = a.to_canonical_bytes();
+            assert_eq!(bytes.len(), 24);
+            assert_eq!(G3::from_canonical_bytes(&bytes), Some(a));
+        }
+        // Each coefficient must be canonical on its own
+        let mut bytes = e2(1, 2).to_canonical_bytes();
+        assert_eq!(E2::from_canonical_bytes(&bytes), Some(e2(1, 2)));
+        bytes[8..].copy_from_slice(&(DEFAULT_FIELD_SIZE as u64).to_le_bytes());
+        assert_eq!(E2::from_canonical_bytes(&bytes), None);
+        assert_eq!(E2::from_canonical_bytes(&bytes[..8]), None);
+    }
+
+    #[test]
+    fn transcript_squeezes_into_the_extension() {
+        let mut t: Transcript = Transcript::new(b"extension-test");
+        let a: E2 = t.challenge(b"beta");
+        let b: E3 = t.challenge(b"beta");
+        // Every coefficient is random, not just the constant one
+        assert!(a.coefficients().iter().all(|c| !c.is_zero()));
+        assert!(b.coefficients().iter().all(|c| !c.is_zero()));
+
+        // Same bytes as the base field challenges, one per coefficient
+        let mut t: Transcript = Transcript::new(b"extension-test");
+        let bytes = t.challenge_bytes(b"beta", E2::RANDOM_BYTES);
+        assert_eq!(
+            a.coefficients(),
+            [
+                F::from_random_bytes(&bytes[..16]),
+                F::from_random_bytes(&bytes[16..])
+            ]
+        );
+    }
+
+    #[test]
+    fn polynomials_evaluate_at_extension_points() {
+        // f(x) = 3 + 2x + x^2
+        let f = Polynomial::new(vec![3, 2, 1]);
+        for z in samples::<2, F>() {
+            let expected = E2::from_u64(3).add(z.scale(F::new(2))).add(z.multiply(z));
+            assert_eq!(f.evaluate_extension(z), expected);
+        }
+        // Base field points give the base field value
+        assert_eq!(
+            f.evaluate_extension(E3::from_u64(5)),
+            E3::from_base(f.evaluate(F::new(5)))
+        );
+    }
+}
@@ -1,96 +1,360 @@
-use crate::finite_field::{FiniteField, FiniteFieldElement};
-use crate::hashing;
+use crate::field::Field;
+use crate::hashing::{Hasher, ToyHasher};
 
-/// Minimal Fiat–Shamir transcript for educational purposes.
+/// Fiat–Shamir transcript shaped as a duplex sponge over `H` (the toy hash by default).
 ///
-/// Accumulates data via a simple rolling hash and derives challenges
-/// deterministically as field elements.
-#[derive(Debug, Clone, Copy)]
-pub struct Transcript {
-    /// Internal sponge-like state (single word, educational)
-    state: i128,
+/// One running digest carries the whole protocol: every message is appended with
+/// a label and folded into the state, and every challenge is squeezed from that
+/// state and then folded back in. A challenge therefore depends on every message
+/// and challenge before it, and no two challenges repeat. It is deliberately not
+/// `Copy`: forking the state takes an explicit `clone`, so a challenge can never be
+/// drawn from a stale implicit copy.
+#[derive(Debug, Clone)]
+pub struct Transcript<H: Hasher = ToyHasher> {
+    /// Running digest of everything absorbed and squeezed so far
+    state: H::Digest,
 }
 
-impl Transcript {
-    pub fn new() -> Self {
-        Transcript { state: 0 }
-    }
-
-    /// Absorb a single integer into the transcript
-    pub fn absorb_i128(&mut self, value: i128) {
-        // Simple rolling hash: hash(state || value)
-        let mixed = self.state.wrapping_add(value.rotate_left(1));
-        self.state = hashing::hash(mixed);
-    }
-
-    /// Absorb a byte slice by chunking into i128 limbs
-    pub fn absorb_bytes(&mut self, bytes: &[u8]) {
-        let mut acc: i128 = 0;
-        let mut count: usize = 0;
-        for &b in bytes {
-            acc = (acc << 8) | (b as i128);
-            count += 1;
-            if count == 15 {
-                // pack up to 15 bytes before absorbing
-                self.absorb_i128(acc);
-                acc = 0;
-                count = 0;
-            }
+/// Operation tags, so an absorbed message can never be mistaken for a squeeze
+const ABSORB: u8 = b'A';
+const SQUEEZE: u8 = b'S';
+
+impl<H: Hasher> Transcript<H> {
+    /// Start a transcript for the protocol named by `label`
+    pub fn new(label: &[u8]) -> Self {
+        let mut t = Transcript {
+            state: H::Digest::default(),
+        };
+        t.append_message(b"protocol", label);
+        t
+    }
+
+    /// Fold one framed operation (tag, label, data, all length-prefixed) into the state
+    fn duplex(&mut self, op: u8, label: &[u8], data: &[u8]) {
+        let mut frame = Vec::with_capacity(1 + 16 + label.len() + data.len());
+        frame.push(op);
+        frame.extend_from_slice(&(label.len() as u64).to_le_bytes());
+        frame.extend_from_slice(label);
+        frame.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        frame.extend_from_slice(data);
+        self.state = H::hash_node(&self.state, &H::hash_bytes(&frame));
+    }
+
+    /// Append raw bytes under `label`
+    pub fn append_message(&mut self, label: &[u8], data: &[u8]) {
+        self.duplex(ABSORB, label, data);
+    }
+
+    /// Append an unsigned integer (sizes, indices)
+    pub fn append_u64(&mut self, label: &[u8], value: u64) {
+        self.append_message(label, &value.to_le_bytes());
+    }
+
+    /// Append a commitment
+    pub fn append_root(&mut self, label: &[u8], root: H::Digest) {
+        self.append_message(label, &H::digest_to_bytes(&root));
+    }
+
+    /// Append field elements by their canonical encodings, together with the
+    /// characteristic, so equal elements always give the same transcript
+    pub fn append_field_elements<F: Field>(&mut self, label: &[u8], values: &[F]) {
+        let mut data = Vec::with_capacity(8 + F::ENCODED_SIZE * values.len());
+        data.extend_from_slice(&F::CHARACTERISTIC.to_le_bytes());
+        for v in values {
+            data.extend_from_slice(&v.to_canonical_bytes());
         }
-        if count > 0 {
-            self.absorb_i128(acc);
+        self.append_message(label, &data);
+    }
+
+    /// Squeeze `n` bytes under `label`. The state is ratcheted first, then output
+    /// blocks are derived from it in counter mode, so the bytes never equal the state.
+    pub fn challenge_bytes(&mut self, label: &[u8], n: usize) -> Vec<u8> {
+        self.duplex(SQUEEZE, label, &(n as u64).to_le_bytes());
+        let mut out = Vec::with_capacity(n);
+        let mut counter: u64 = 0;
+        while out.len() < n {
+            let block = H::hash_node(&self.state, &H::hash_bytes(&counter.to_le_bytes()));
+            out.extend(H::digest_to_bytes(&block));
+            counter += 1;
         }
+        out.truncate(n);
+        out
     }
 
-    /// Derive a challenge as a field element in the provided field
-    pub fn challenge(&mut self, field: FiniteField) -> FiniteFieldElement {
-        // domain separate by hashing state again
-        self.state = hashing::hash(self.state.wrapping_add(0x9e37_79b9_7f4a_7c15));
-        // Map hash to field by reduction
-        FiniteFieldElement::new_fielded(self.state, field)
+    /// Derive a challenge as an element of the field `F`
+    pub fn challenge<F: Field>(&mut self, label: &[u8]) -> F {
+        let bytes = self.challenge_bytes(label, F::RANDOM_BYTES);
+        F::from_random_bytes(&bytes)
+    }
+
+    /// Derive `count` indices in 0..bound, uniformly: each draw takes 8 fresh bytes,
+    /// masks them to the next power of two above `bound` and is rejected if not
+    /// below `bound`, so no index is more likely than another. With `distinct`,
+    /// repeated indices are rejected as well (needs `count <= bound`).
+    pub fn challenge_indices(
+        &mut self,
+        label: &[u8],
+        bound: usize,
+        count: usize,
+        distinct: bool,
+    ) -> Vec<usize> {
+        assert!(bound > 0, "cannot sample indices from an empty range");
+        assert!(
+            !distinct || count <= bound,
+            "cannot draw {} distinct indices below {}",
+            count,
+            bound
+        );
+        let mask = (bound as u64).next_power_of_two() - 1;
+        let mut indices = Vec::with_capacity(count);
+        while indices.len() < count {
+            let bytes = self.challenge_bytes(label, 8);
+            let candidate = (u64::from_le_bytes(bytes.try_into().unwrap()) & mask) as usize;
+            if candidate < bound && !(distinct && indices.contains(&candidate)) {
+                indices.push(candidate);
+            }
+        }
+        indices
+    }
+
+    /// Proof-of-work digest of `nonce` against the current state
+    fn pow_digest(&self, nonce: u64) -> H::Digest {
+        let mut data = b"pow".to_vec();
+        data.extend_from_slice(&nonce.to_le_bytes());
+        H::hash_node(&self.state, &H::hash_bytes(&data))
+    }
+
+    /// Prover side of grinding: find the first nonce whose proof-of-work digest
+    /// starts with `bits` zero bits, append it and return it. Costs about 2^bits
+    /// hashes, and every later challenge costs a cheating prover as much to redraw.
+    pub fn grind(&mut self, bits: u32) -> u64 {
+        let nonce = (0..u64::MAX)
+            .find(|&nonce| leading_zero_bits(&H::digest_to_bytes(&self.pow_digest(nonce))) >= bits)
+            .expect("no proof-of-work nonce found");
+        self.append_u64(b"pow_nonce", nonce);
+        nonce
+    }
+
+    /// Verifier side of grinding: check `nonce` and append it, leaving the
+    /// transcript in the same state as `grind` did for the prover
+    pub fn verify_pow(&mut self, nonce: u64, bits: u32) -> bool {
+        let valid = leading_zero_bits(&H::digest_to_bytes(&self.pow_digest(nonce))) >= bits;
+        self.append_u64(b"pow_nonce", nonce);
+        valid
+    }
+
+    /// Derive `n` challenges under the same label
+    pub fn challenges<F: Field>(&mut self, label: &[u8], n: usize) -> Vec<F> {
+        (0..n).map(|_| self.challenge(label)).collect()
     }
 }
 
+/// Number of zero bits at the start of `bytes`, most significant bit of each byte first
+fn leading_zero_bits(bytes: &[u8]) -> u32 {
+    let mut zeros = 0;
+    for &b in bytes {
+        zeros += b.leading_zeros();
+        if b != 0 {
+            break;
+        }
+    }
+    zeros
+}
+
 #[cfg(test)]
 mod tests {
     use crate::constants::DEFAULT_FIELD_SIZE;
+    use crate::finite_field::FiniteFieldElement;
+    use crate::hashing::Sha256;
 
     use super::*;
 
+    type F = FiniteFieldElement;
+
     #[test]
     fn determinism_same_inputs_same_challenges() {
-        let field = FiniteField::new(DEFAULT_FIELD_SIZE);
-        let mut t1 = Transcript::new();
-        let mut t2 = Transcript::new();
+        let mut t1: Transcript = Transcript::new(b"test");
+        let mut t2: Transcript = Transcript::new(b"test");
 
-        t1.absorb_i128(42);
-        t1.absorb_bytes(b"hello");
-        let c1a = t1.challenge(field);
-        let c1b = t1.challenge(field);
+        t1.append_u64(b"n", 42);
+        t1.append_message(b"greeting", b"hello");
+        let c1a = t1.challenge::<F>(b"c");
+        let c1b = t1.challenge::<F>(b"c");
 
-        t2.absorb_i128(42);
-        t2.absorb_bytes(b"hello");
-        let c2a = t2.challenge(field);
-        let c2b = t2.challenge(field);
+        t2.append_u64(b"n", 42);
+        t2.append_message(b"greeting", b"hello");
+        let c2a = t2.challenge::<F>(b"c");
+        let c2b = t2.challenge::<F>(b"c");
 
         assert_eq!(c1a.value, c2a.value);
         assert_eq!(c1b.value, c2b.value);
-        assert_eq!(c1a.field.prime, field.prime);
-        assert_eq!(c1b.field.prime, field.prime);
+        assert_ne!(c1a, c1b);
     }
 
     #[test]
     fn different_absorbs_change_challenge() {
-        let field = FiniteField::new(DEFAULT_FIELD_SIZE);
-        let mut t1 = Transcript::new();
-        let mut t2 = Transcript::new();
+        let mut t1: Transcript = Transcript::new(b"test");
+        let mut t2: Transcript = Transcript::new(b"test");
 
-        t1.absorb_i128(1);
-        let c1 = t1.challenge(field);
+        t1.append_u64(b"n", 1);
+        let c1 = t1.challenge::<F>(b"c");
 
-        t2.absorb_i128(2);
-        let c2 = t2.challenge(field);
+        t2.append_u64(b"n", 2);
+        let c2 = t2.challenge::<F>(b"c");
 
         assert_ne!(c1.value, c2.value);
     }
+
+    #[test]
+    fn labels_separate_messages() {
+        let squeeze = |f: &dyn Fn(&mut Transcript<Sha256>)| {
+            let mut t = Transcript::<Sha256>::new(b"test");
+            f(&mut t);
+            t.challenge::<F>(b"c")
+        };
+
+        let base = squeeze(&|t| t.append_message(b"a", b"xy"));
+        // Same bytes under another label
+        assert_ne!(base, squeeze(&|t| t.append_message(b"b", b"xy")));
+        // Label and data boundaries shifted
+        assert_ne!(base, squeeze(&|t| t.append_message(b"ax", b"y")));
+        // Same data split over two messages
+        assert_ne!(
+            base,
+            squeeze(&|t| {
+                t.append_message(b"a", b"x");
+                t.append_message(b"a", b"y");
+            })
+        );
+        // Another protocol name
+        let mut other = Transcript::<Sha256>::new(b"other");
+        other.append_message(b"a", b"xy");
+        assert_ne!(base, other.challenge::<F>(b"c"));
+        // Challenge label matters too
+        let mut t = Transcript::<Sha256>::new(b"test");
+        t.append_message(b"a", b"xy");
+        assert_ne!(base, t.challenge::<F>(b"d"));
+    }
+
+    #[test]
+    fn every_message_influences_later_challenges() {
+        let run = |first: u64| {
+            let mut t: Transcript = Transcript::new(b"test");
+            t.append_u64(b"first", first);
+            let early = t.challenge::<F>(b"early");
+            t.append_field_elements(b"values", &[F::new(5)]);
+            t.append_root(b"root", 1234);
+            let late = t.challenges::<F>(b"late", 3);
+            (early, late)
+        };
+        let (early_a, late_a) = run(1);
+        let (early_b, late_b) = run(2);
+        assert_ne!(early_a, early_b);
+        for (a, b) in late_a.iter().zip(late_b.iter()) {
+            assert_ne!(a, b);
+        }
+    }
+
+    #[test]
+    fn field_elements_are_appended_canonically() {
+        let mut t1: Transcript = Transcript::new(b"test");
+        let mut t2: Transcript = Transcript::new(b"test");
+        t1.append_field_elements(b"v", &[F::new(-1)]);
+        t2.append_field_elements(b"v", &[F::new(DEFAULT_FIELD_SIZE - 1)]);
+        assert_eq!(t1.challenge::<F>(b"c"), t2.challenge::<F>(b"c"));
+    }
+
+    #[test]
+    fn field_elements_carry_their_characteristic() {
+        // The same canonical value in another field gives another transcript
+        let mut t1: Transcript = Transcript::new(b"test");
+        let mut t2: Transcript = Transcript::new(b"test");
+        t1.append_field_elements(b"v", &[F::new(5)]);
+        t2.append_field_elements(b"v", &[FiniteFieldElement::<17>::from_i128(5)]);
+        assert_ne!(t1.challenge::<F>(b"c"), t2.challenge::<F>(b"c"));
+
+        // Challenges land in the requested field
+        let small = t1.challenge::<FiniteFieldElement<17>>(b"c");
+        assert!(small.value < 17);
+    }
+
+    #[test]
+    fn challenge_indices_are_uniform() {
+        // Chi-squared test over a domain that is not a power of two
+        let bound = 6;
+        let draws = 60_000;
+        let mut t: Transcript = Transcript::new(b"test");
+        let mut counts = [0usize; 6];
+        for i in t.challenge_indices(b"q", bound, draws, false) {
+            counts[i] += 1;
+        }
+        let expected = (draws / bound) as f64;
+        let chi_squared: f64 = counts
+            .iter()
+            .map(|&c| (c as f64 - expected).powi(2) / expected)
+            .sum();
+        // 5 degrees of freedom: P(chi² > 20.5) = 0.001
+        assert!(chi_squared < 20.5, "counts {:?}", counts);
+    }
+
+    #[test]
+    fn challenge_indices_can_be_distinct() {
+        let mut t: Transcript = Transcript::new(b"test");
+        let mut indices = t.challenge_indices(b"q", 10, 10, true);
+        assert!(indices.iter().all(|&i| i < 10));
+        indices.sort();
+        assert_eq!(indices, (0..10).collect::<Vec<_>>());
+
+        let mut t: Transcript = Transcript::new(b"test");
+        let indices = t.challenge_indices(b"q", 1000, 30, true);
+        assert_eq!(indices.len(), 30);
+        for (k, i) in indices.iter().enumerate() {
+            assert!(*i < 1000);
+            assert!(!indices[..k].contains(i));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_many_distinct_indices_panics() {
+        let mut t: Transcript = Transcript::new(b"test");
+        t.challenge_indices(b"q", 4, 5, true);
+    }
+
+    #[test]
+    fn leading_zero_bits_counts_across_bytes() {
+        assert_eq!(leading_zero_bits(&[0x80, 0x00]), 0);
+        assert_eq!(leading_zero_bits(&[0x01, 0xff]), 7);
+        assert_eq!(leading_zero_bits(&[0x00, 0x10]), 11);
+        assert_eq!(leading_zero_bits(&[0x00, 0x00]), 16);
+    }
+
+    #[test]
+    fn grinding_nonce_verifies() {
+        let mut prover = Transcript::<Sha256>::new(b"test");
+        let mut verifier = Transcript::<Sha256>::new(b"test");
+        prover.append_u64(b"n", 1);
+        verifier.append_u64(b"n", 1);
+
+        let nonce = prover.grind(10);
+        assert!(leading_zero_bits(&Sha256::digest_to_bytes(&verifier.pow_digest(nonce))) >= 10);
+        assert!(verifier.clone().verify_pow(nonce, 10));
+        // The search returns the first valid nonce, so earlier ones fail
+        if nonce > 0 {
+            assert!(!verifier.clone().verify_pow(nonce - 1, 10));
+        }
+        // Grinding with no difficulty takes the first nonce
+        assert_eq!(Transcript::<Sha256>::new(b"test").grind(0), 0);
+
+        // Both sides continue from the same state
+        assert!(verifier.verify_pow(nonce, 10));
+        assert_eq!(prover.challenge::<F>(b"c"), verifier.challenge::<F>(b"c"));
+    }
+
+    #[test]
+    fn challenge_bytes_have_requested_length() {
+        let mut t = Transcript::<Sha256>::new(b"test");
+        assert_eq!(t.challenge_bytes(b"c", 0).len(), 0);
+        assert_eq!(t.challenge_bytes(b"c", 100).len(), 100);
+    }
 }
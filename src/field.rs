@@ -0,0 +1,229 @@
+use core::fmt;
+
+/// Errors from looking up structure in the multiplicative group of a field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldError {
+    /// Roots of unity of order 0 do not exist
+    ZeroOrder,
+    /// The requested order does not divide p - 1, so no element has it
+    OrderDoesNotDivideGroupOrder { order: usize, group_order: u64 },
+    /// Zero has no inverse; `index` is its position in a batch
+    ZeroInverse { index: usize },
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldError::ZeroOrder => write!(f, "root of unity order must be positive"),
+            FieldError::OrderDoesNotDivideGroupOrder { order, group_order } => write!(
+                f,
+                "no root of unity of order {} (it does not divide p - 1 = {})",
+                order, group_order
+            ),
+            FieldError::ZeroInverse { index } => {
+                write!(
+                    f,
+                    "element {} of the batch is zero and has no inverse",
+                    index
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for FieldError {}
+
+/// A finite field. Everything that computes over field elements (polynomials,
+/// domains, traces, FRI, the prover and the verifier) is generic over it, so
+/// values from two different fields cannot be mixed.
+pub trait Field:
+    Copy + fmt::Debug + fmt::Display + Default + PartialEq + Eq + Send + Sync + 'static
+{
+    const ZERO: Self;
+    const ONE: Self;
+    /// The prime p of the field
+    const CHARACTERISTIC: u64;
+    /// Largest k such that 2^k divides the order of the multiplicative group,
+    /// i.e. the largest power-of-two subgroup (and NTT size) the field supports.
+    /// Extension fields report the subgroups of their base field.
+    const TWO_ADICITY: u32;
+    /// Length of `to_canonical_bytes`
+    const ENCODED_SIZE: usize;
+    /// Number of random bytes `from_random_bytes` reduces into an element
+    const RANDOM_BYTES: usize = 16;
+
+    /// The element `value` mod p
+    fn from_u64(value: u64) -> Self;
+
+    /// The element `value` mod p, for negative values too
+    fn from_i128(value: i128) -> Self {
+        let p = Self::CHARACTERISTIC as i128;
+        Self::from_u64(value.rem_euclid(p) as u64)
+    }
+
+    fn add(&self, other: Self) -> Self;
+    fn subtract(&self, other: Self) -> Self;
+    fn multiply(&self, other: Self) -> Self;
+    fn negate(&self) -> Self;
+
+    /// Multiplicative inverse; zero has none and maps to zero
+    fn inverse(&self) -> Self;
+
+    fn pow(&self, exponent: u64) -> Self {
+        // Fast exponentiation by squaring
+        let mut result = Self::ONE;
+        let mut base = *self;
+        let mut exp = exponent;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.multiply(base);
+            }
+            base = base.multiply(base);
+            exp >>= 1;
+        }
+        result
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == Self::ZERO
+    }
+
+    /// A generator of the multiplicative group (of the base field's group for
+    /// extension fields, since domains and cosets live there)
+    fn multiplicative_generator() -> Self;
+
+    /// Element of multiplicative order exactly `n`: g^((p - 1) / n) for the generator g
+    fn primitive_root_of_unity(n: usize) -> Result<Self, FieldError> {
+        let group_order = Self::CHARACTERISTIC - 1;
+        if n == 0 {
+            return Err(FieldError::ZeroOrder);
+        }
+        if group_order % (n as u64) != 0 {
+            return Err(FieldError::OrderDoesNotDivideGroupOrder {
+                order: n,
+                group_order,
+            });
+        }
+        Ok(Self::multiplicative_generator().pow(group_order / n as u64))
+    }
+
+    /// Unique little-endian encoding of `ENCODED_SIZE` bytes
+    fn to_canonical_bytes(&self) -> Vec<u8>;
+
+    /// Inverse of `to_canonical_bytes`; None for a wrong length or an
+    /// unreduced value
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self>;
+
+    /// Map `RANDOM_BYTES` uniform bytes to an element, with a bias of about
+    /// 2^-64 for fields below 2^64
+    fn from_random_bytes(bytes: &[u8]) -> Self {
+        let mut wide = [0u8; 16];
+        let len = bytes.len().min(16);
+        wide[..len].copy_from_slice(&bytes[..len]);
+        let reduced = u128::from_le_bytes(wide) % Self::CHARACTERISTIC as u128;
+        Self::from_u64(reduced as u64)
+    }
+}
+
+/// Invert every element in place with Montgomery's trick: one inversion and about
+/// 3n multiplications instead of n inversions. Fails, leaving `values` unchanged,
+/// if an element is zero.
+pub fn batch_inverse<F: Field>(values: &mut [F]) -> Result<(), FieldError> {
+    if let Some(index) = values.iter().position(|v| v.is_zero()) {
+        return Err(FieldError::ZeroInverse { index });
+    }
+    batch_inverse_skipping_zeros(values);
+    Ok(())
+}
+
+/// `batch_inverse`, but zeros are left as they are (like `Field::inverse`, which
+/// maps zero to zero)
+pub fn batch_inverse_skipping_zeros<F: Field>(values: &mut [F]) {
+    // prefix[i] is the product of the non-zero values before i
+    let mut prefix = Vec::with_capacity(values.len());
+    let mut product = F::ONE;
+    for v in values.iter() {
+        prefix.push(product);
+        if !v.is_zero() {
+            product = product.multiply(*v);
+        }
+    }
+
+    // Walk back with the inverse of the running product: at i it is the inverse
+    // of the product up to and including i
+    let mut inverse = product.inverse();
+    for (v, before) in values.iter_mut().zip(prefix).rev() {
+        if v.is_zero() {
+            continue;
+        }
+        let value = *v;
+        *v = inverse.multiply(before);
+        inverse = inverse.multiply(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extension_field::ExtensionField;
+    use crate::finite_field::FiniteFieldElement;
+    use crate::goldilocks::Goldilocks;
+
+    type F = FiniteFieldElement;
+
+    fn elements<E: Field>(values: &[u64]) -> Vec<E> {
+        values.iter().map(|&v| E::from_u64(v)).collect()
+    }
+
+    fn check_batch<E: Field>() {
+        let original = elements::<E>(&[1, 2, 3, 5, 1 << 20, 12345678, E::CHARACTERISTIC - 1]);
+        let mut values = original.clone();
+        batch_inverse(&mut values).unwrap();
+        for (inverse, value) in values.iter().zip(&original) {
+            assert_eq!(*inverse, value.inverse());
+            assert_eq!(inverse.multiply(*value), E::ONE);
+        }
+    }
+
+    #[test]
+    fn batch_inverse_matches_single_inverses() {
+        check_batch::<F>();
+        check_batch::<Goldilocks>();
+        check_batch::<ExtensionField<2>>();
+
+        let mut empty: Vec<F> = Vec::new();
+        assert_eq!(batch_inverse(&mut empty), Ok(()));
+        let mut single = vec![F::new(7)];
+        batch_inverse(&mut single).unwrap();
+        assert_eq!(single, vec![F::new(7).inverse()]);
+    }
+
+    #[test]
+    fn batch_inverse_refuses_zeros() {
+        let original = elements::<F>(&[3, 0, 4, 0]);
+        let mut values = original.clone();
+        assert_eq!(
+            batch_inverse(&mut values),
+            Err(FieldError::ZeroInverse { index: 1 })
+        );
+        assert_eq!(values, original, "nothing is inverted on failure");
+    }
+
+    #[test]
+    fn zeros_can_be_skipped() {
+        let mut values = elements::<F>(&[0, 3, 0, 4, 0]);
+        batch_inverse_skipping_zeros(&mut values);
+        let expected = [
+            F::ZERO,
+            F::new(3).inverse(),
+            F::ZERO,
+            F::new(4).inverse(),
+            F::ZERO,
+        ];
+        assert_eq!(values, expected);
+
+        let mut zeros = vec![F::ZERO; 3];
+        batch_inverse_skipping_zeros(&mut zeros);
+        assert_eq!(zeros, vec![F::ZERO; 3]);
+    }
+}
@@ -1,202 +1,285 @@
-use crate::{constants::DEFAULT_FIELD_SIZE, hashing};
-
-#[derive(Debug, Clone, Copy)]
-pub struct FiniteField {
-    pub prime: i128,
+use core::fmt;
+
+use crate::constants::DEFAULT_FIELD_SIZE;
+use crate::field::Field;
+
+/// Distinct prime factors of `n` by trial division. Fast for p - 1 of the fields
+/// used here, which are a large power of two times small factors.
+fn prime_factors(mut n: u64) -> Vec<u64> {
+    let mut factors = Vec::new();
+    let mut d = 2;
+    while d * d <= n {
+        if n.is_multiple_of(d) {
+            factors.push(d);
+            while n.is_multiple_of(d) {
+                n /= d;
+            }
+        }
+        d += if d == 2 { 1 } else { 2 };
+    }
+    if n > 1 {
+        factors.push(n);
+    }
+    factors
 }
 
-impl FiniteField {
-    pub fn new(prime: i128) -> Self {
-        FiniteField { prime }
+/// Smallest generator of the multiplicative group of F: g such that
+/// g^((p - 1) / q) != 1 for every prime factor q of p - 1
+pub(crate) fn smallest_generator<F: Field>() -> F {
+    let group_order = F::CHARACTERISTIC - 1;
+    let factors = prime_factors(group_order);
+    let mut candidate = 2;
+    loop {
+        let g = F::from_u64(candidate);
+        if factors.iter().all(|&q| g.pow(group_order / q) != F::ONE) {
+            return g;
+        }
+        candidate += 1;
     }
+}
 
-    pub fn element(&self, value: i128) -> FiniteFieldElement {
-        FiniteFieldElement::new_fielded(value, *self)
+/// base^exp mod p, usable in constants
+const fn pow_mod(base: u64, mut exp: u64, p: u64) -> u64 {
+    let (mut acc, mut base) = (1u128, base as u128 % p as u128);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            acc = acc * base % p as u128;
+        }
+        base = base * base % p as u128;
+        exp >>= 1;
     }
+    acc as u64
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct FiniteFieldElement {
-    pub value: i128,
-    pub field: FiniteField,
+/// Whether w is no d-th power mod the prime p, i.e. d divides p - 1, w != 0 and
+/// w^((p - 1) / d) != 1 (when d does not divide p - 1, every element is a d-th power)
+pub const fn is_non_residue(w: u64, p: u64, d: u64) -> bool {
+    d != 0 && (p - 1).is_multiple_of(d) && !w.is_multiple_of(p) && pow_mod(w, (p - 1) / d, p) != 1
 }
 
-impl PartialEq for FiniteFieldElement {
-    fn eq(&self, other: &Self) -> bool {
-        // Two field elements are equal if they're in the same field and have the same canonical value
-        self.field.prime == other.field.prime && self.value == other.value
+/// Smallest w >= 2 that is not a d-th power mod the prime p, or 0 if there is none
+pub const fn smallest_non_residue(p: u64, d: u64) -> u64 {
+    if d == 0 || !(p - 1).is_multiple_of(d) {
+        return 0;
+    }
+    let mut w = 2;
+    while w < p {
+        if is_non_residue(w, p, d) {
+            return w;
+        }
+        w += 1;
     }
+    0
 }
 
-impl Eq for FiniteFieldElement {}
+/// Deterministic Miller–Rabin: the first twelve primes as bases are enough for
+/// every n below 3.3 · 10^24, and n² fits in a u128
+pub fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if n < 2 {
+        return false;
+    }
+    if let Some(&b) = BASES.iter().find(|&&b| n.is_multiple_of(b)) {
+        return n == b;
+    }
 
-/// TODO: consider what to do when i128 overflows
-impl FiniteFieldElement {
-    const DEFAULT_FIELD: FiniteField = FiniteField {
-        prime: DEFAULT_FIELD_SIZE,
+    let mul_mod = |a: u64, b: u64| (a as u128 * b as u128 % n as u128) as u64;
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    let pow_mod = |mut base: u64, mut exp: u64| {
+        let mut acc = 1;
+        base %= n;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                acc = mul_mod(acc, base);
+            }
+            base = mul_mod(base, base);
+            exp >>= 1;
+        }
+        acc
     };
+    BASES.iter().all(|&a| {
+        let mut x = pow_mod(a, d);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
 
-    pub const ZERO: Self = FiniteFieldElement {
-        value: 0,
-        field: Self::DEFAULT_FIELD,
-    };
+/// Element of the prime field of order `P`, stored canonically in [0, P). The
+/// modulus is part of the type, so elements of different fields cannot meet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FiniteFieldElement<const P: i128 = DEFAULT_FIELD_SIZE> {
+    pub value: i128,
+}
+
+impl<const P: i128> FiniteFieldElement<P> {
+    /// Products of two canonical values must fit in an i128
+    const MODULUS_FITS: () = assert!(P > 2 && P < 1 << 62, "modulus must be in 3..2^62");
 
-    pub fn new(value: i128) -> Self {
-        let value_mod = value % Self::DEFAULT_FIELD.prime;
+    /// The element `value` mod P
+    pub const fn from_value(value: i128) -> Self {
+        let () = Self::MODULUS_FITS;
         FiniteFieldElement {
-            value: value_mod,
-            field: Self::DEFAULT_FIELD,
+            value: value.rem_euclid(P),
         }
     }
+}
 
-    pub fn new_fielded(value: i128, field: FiniteField) -> Self {
-        let value_mod = value % field.prime;
-        FiniteFieldElement {
-            value: value_mod,
-            field,
-        }
+impl FiniteFieldElement {
+    /// Element of the default field
+    pub const fn new(value: i128) -> Self {
+        Self::from_value(value)
     }
+}
 
-    pub fn add(&self, other: Self) -> Self {
-        let new_value = (self.value + other.value) % self.field.prime;
-        FiniteFieldElement::new_fielded(new_value, self.field)
+/// Zero
+impl<const P: i128> Default for FiniteFieldElement<P> {
+    fn default() -> Self {
+        Self::ZERO
     }
+}
 
-    pub fn subtract(&self, other: Self) -> Self {
-        // Add prime (first) to make sure the value stays positive
-        let new_value = (self.value + self.field.prime - other.value) % self.field.prime;
-        FiniteFieldElement::new_fielded(new_value, self.field)
+impl<const P: i128> fmt::Display for FiniteFieldElement<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
     }
+}
+
+impl<const P: i128> Field for FiniteFieldElement<P> {
+    const ZERO: Self = Self::from_value(0);
+    const ONE: Self = Self::from_value(1);
+    const CHARACTERISTIC: u64 = P as u64;
+    const TWO_ADICITY: u32 = (P - 1).trailing_zeros();
+    const ENCODED_SIZE: usize = 8;
 
-    pub fn multiply(&self, other: Self) -> Self {
-        assert_eq!(self.field.prime, other.field.prime);
-        let new_value = (self.value * other.value) % self.field.prime;
-        FiniteFieldElement::new_fielded(new_value, self.field)
+    fn from_u64(value: u64) -> Self {
+        Self::from_value(value as i128)
     }
 
-    pub fn pow(&self, exponent: i128) -> Self {
-        // Fast exponentiation by squaring
-        let mut result = FiniteFieldElement::new_fielded(1, self.field);
-        let mut base = *self;
-        let mut exp = exponent;
-        while exp > 0 {
-            if exp % 2 == 1 {
-                result = result.multiply(base);
-            }
-            base = base.multiply(base);
-            exp /= 2;
-        }
-        result
+    fn from_i128(value: i128) -> Self {
+        Self::from_value(value)
     }
 
-    pub fn inverse(&self) -> Self {
-        // Fermat's little theorem: a^(p-2) mod p
-        self.pow(self.field.prime - 2)
+    fn add(&self, other: Self) -> Self {
+        Self::from_value(self.value + other.value)
+    }
+
+    fn subtract(&self, other: Self) -> Self {
+        Self::from_value(self.value - other.value)
+    }
+
+    fn multiply(&self, other: Self) -> Self {
+        Self::from_value(self.value * other.value)
     }
 
-    pub fn negate(&self) -> Self {
+    fn negate(&self) -> Self {
         // Negation in finite field: -a = p - a (mod p)
-        let negated_value = (self.field.prime - self.value) % self.field.prime;
-        FiniteFieldElement::new_fielded(negated_value, self.field)
+        Self::from_value(P - self.value)
+    }
+
+    fn inverse(&self) -> Self {
+        // Fermat's little theorem: a^(p-2) mod p
+        self.pow(P as u64 - 2)
+    }
+
+    fn multiplicative_generator() -> Self {
+        smallest_generator()
     }
 
-    pub fn is_zero(&self) -> bool {
-        self.value == 0
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        (self.value as u64).to_le_bytes().to_vec()
     }
 
-    pub fn hash(&self) -> i128 {
-        hashing::hash(self.value)
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self> {
+        let value = u64::from_le_bytes(bytes.try_into().ok()?) as i128;
+        (value < P).then_some(FiniteFieldElement { value })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::field::FieldError;
+
+    type F5 = FiniteFieldElement<5>;
+    type F7 = FiniteFieldElement<7>;
+    type F17 = FiniteFieldElement<17>;
+    type F = FiniteFieldElement;
 
     #[test]
     fn new() {
-        let field: FiniteField = FiniteField::new(5);
-        let elem: FiniteFieldElement = FiniteFieldElement::new_fielded(4, field);
-
+        let elem = create(4);
         assert_eq!(elem.value, 4);
-        assert_eq!(elem.field.prime, 5);
+        assert_eq!(F5::CHARACTERISTIC, 5);
+
+        // Values are stored canonically
+        assert_eq!(create(-1).value, 4);
+        assert_eq!(FiniteFieldElement::new(-1).value, DEFAULT_FIELD_SIZE - 1);
     }
 
     #[test]
     fn add() {
-        let f: FiniteField = FiniteField::new(5);
-
-        assert_eq!(create(1, f).add(create(3, f)).value, 4);
-        assert_eq!(create(4, f).add(create(3, f)).value, 2);
-        assert_eq!(create(14, f).add(create(3, f)).value, 2);
-        assert_eq!(create(4, f).add(create(14, f)).value, 3);
+        assert_eq!(create(1).add(create(3)).value, 4);
+        assert_eq!(create(4).add(create(3)).value, 2);
+        assert_eq!(create(14).add(create(3)).value, 2);
+        assert_eq!(create(4).add(create(14)).value, 3);
     }
 
     #[test]
     fn subtract() {
-        let f: FiniteField = FiniteField::new(5);
-
-        assert_eq!(create(4, f).subtract(create(1, f)).value, 3);
-        assert_eq!(create(3, f).subtract(create(4, f)).value, 4);
-        assert_eq!(create(14, f).subtract(create(3, f)).value, 1);
-        assert_eq!(create(4, f).subtract(create(14, f)).value, 0);
+        assert_eq!(create(4).subtract(create(1)).value, 3);
+        assert_eq!(create(3).subtract(create(4)).value, 4);
+        assert_eq!(create(14).subtract(create(3)).value, 1);
+        assert_eq!(create(4).subtract(create(14)).value, 0);
     }
 
     #[test]
     fn multiply() {
-        let f: FiniteField = FiniteField::new(5);
-
-        assert_eq!(create(2, f).multiply(create(0, f)).value, 0);
-        assert_eq!(create(0, f).multiply(create(2, f)).value, 0);
+        assert_eq!(create(2).multiply(create(0)).value, 0);
+        assert_eq!(create(0).multiply(create(2)).value, 0);
 
-        assert_eq!(create(2, f).multiply(create(2, f)).value, 4);
-        assert_eq!(create(3, f).multiply(create(4, f)).value, 2);
-        assert_eq!(create(14, f).multiply(create(3, f)).value, 2);
-        assert_eq!(create(4, f).multiply(create(14, f)).value, 1);
+        assert_eq!(create(2).multiply(create(2)).value, 4);
+        assert_eq!(create(3).multiply(create(4)).value, 2);
+        assert_eq!(create(14).multiply(create(3)).value, 2);
+        assert_eq!(create(4).multiply(create(14)).value, 1);
     }
 
     #[test]
     fn pow() {
-        let f: FiniteField = FiniteField::new(5);
-        assert_eq!(create(2, f).pow(0).value, 1);
-        assert_eq!(create(0, f).pow(0).value, 1);
-        assert_eq!(create(2, f).pow(1).value, 2);
-        assert_eq!(create(2, f).pow(3).value, 3);
-        assert_eq!(create(3, f).pow(2).value, 4);
+        assert_eq!(create(2).pow(0).value, 1);
+        assert_eq!(create(0).pow(0).value, 1);
+        assert_eq!(create(2).pow(1).value, 2);
+        assert_eq!(create(2).pow(3).value, 3);
+        assert_eq!(create(3).pow(2).value, 4);
     }
 
     #[test]
     fn inverse() {
-        let f: FiniteField = FiniteField::new(5);
-        assert_eq!(create(2, f).inverse().value, 3); // 2*3=6≡1
-        assert_eq!(create(3, f).inverse().value, 2); // 3*2=6≡1
-        assert_eq!(create(4, f).inverse().value, 4); // 4*4=16≡1
-    }
-
-    #[test]
-    fn hash() {
-        let f: FiniteField = FiniteField::new(5);
-
-        assert_ne!(create(1, f).hash(), create(2, f).hash());
-        assert_ne!(create(0, f).hash(), create(4, f).hash());
-
-        assert_eq!(create(1, f).hash(), create(6, f).hash());
+        assert_eq!(create(2).inverse().value, 3); // 2*3=6≡1
+        assert_eq!(create(3).inverse().value, 2); // 3*2=6≡1
+        assert_eq!(create(4).inverse().value, 4); // 4*4=16≡1
     }
 
     #[test]
     fn negate() {
-        let f: FiniteField = FiniteField::new(5);
-
         // Test negation: -a = p - a (mod p)
-        assert_eq!(create(0, f).negate().value, 0); // -0 = 0
-        assert_eq!(create(1, f).negate().value, 4); // -1 = 5-1 = 4
-        assert_eq!(create(2, f).negate().value, 3); // -2 = 5-2 = 3
-        assert_eq!(create(3, f).negate().value, 2); // -3 = 5-3 = 2
-        assert_eq!(create(4, f).negate().value, 1); // -4 = 5-4 = 1
+        assert_eq!(create(0).negate().value, 0); // -0 = 0
+        assert_eq!(create(1).negate().value, 4); // -1 = 5-1 = 4
+        assert_eq!(create(2).negate().value, 3); // -2 = 5-2 = 3
+        assert_eq!(create(3).negate().value, 2); // -3 = 5-3 = 2
+        assert_eq!(create(4).negate().value, 1); // -4 = 5-4 = 1
 
         // Test that a + (-a) = 0
         for i in 0..5 {
-            let elem = create(i, f);
+            let elem = create(i);
             let neg_elem = elem.negate();
             let sum = elem.add(neg_elem);
             assert_eq!(sum.value, 0, "{} + (-{}) should equal 0", i, i);
@@ -205,44 +288,141 @@ mod tests {
 
     #[test]
     fn equality() {
-        let f: FiniteField = FiniteField::new(5);
-        let g: FiniteField = FiniteField::new(7);
-
         // Same field, same value
-        assert_eq!(create(3, f), create(3, f));
-        assert_eq!(create(0, f), create(0, f));
+        assert_eq!(create(3), create(3));
+        assert_eq!(create(0), create(0));
 
         // Same field, different values
-        assert_ne!(create(3, f), create(4, f));
-        assert_ne!(create(0, f), create(1, f));
-
-        // Different fields, same value
-        assert_ne!(create(3, f), create(3, g));
+        assert_ne!(create(3), create(4));
+        assert_ne!(create(0), create(1));
 
         // Test that equivalent values in same field are equal
-        assert_eq!(create(3, f), create(8, f)); // 3 ≡ 8 (mod 5)
-        assert_eq!(create(1, f), create(6, f)); // 1 ≡ 6 (mod 5)
+        assert_eq!(create(3), create(8)); // 3 ≡ 8 (mod 5)
+        assert_eq!(create(1), create(6)); // 1 ≡ 6 (mod 5)
+        assert_eq!(create(-1), create(4)); // -1 ≡ 4 (mod 5)
+
+        // Elements of different fields have different types, so comparing
+        // F5 with F7 does not compile; only their encodings can be compared
+        assert_eq!(
+            create(3).to_canonical_bytes(),
+            F7::from_i128(3).to_canonical_bytes()
+        );
     }
 
     #[test]
     fn is_zero() {
-        let f: FiniteField = FiniteField::new(5);
-
         // Test zero elements
-        assert!(create(0, f).is_zero());
-        assert!(create(5, f).is_zero()); // 5 ≡ 0 (mod 5)
-        assert!(create(10, f).is_zero()); // 10 ≡ 0 (mod 5)
+        assert!(create(0).is_zero());
+        assert!(create(5).is_zero()); // 5 ≡ 0 (mod 5)
+        assert!(create(10).is_zero()); // 10 ≡ 0 (mod 5)
 
         // Test non-zero elements
-        assert!(!create(1, f).is_zero());
-        assert!(!create(2, f).is_zero());
-        assert!(!create(3, f).is_zero());
-        assert!(!create(4, f).is_zero());
-        assert!(!create(6, f).is_zero()); // 6 ≡ 1 (mod 5)
+        assert!(!create(1).is_zero());
+        assert!(!create(2).is_zero());
+        assert!(!create(3).is_zero());
+        assert!(!create(4).is_zero());
+        assert!(!create(6).is_zero()); // 6 ≡ 1 (mod 5)
+    }
+
+    #[test]
+    fn canonical_bytes() {
+        let x = FiniteFieldElement::new(-2);
+        let bytes = x.to_canonical_bytes();
+        assert_eq!(bytes.len(), F::ENCODED_SIZE);
+        assert_eq!(F::from_canonical_bytes(&bytes), Some(x));
+
+        // Unreduced values and wrong lengths are refused
+        assert_eq!(F5::from_canonical_bytes(&5u64.to_le_bytes()), None);
+        assert_eq!(F5::from_canonical_bytes(&[1, 0, 0, 0]), None);
+    }
+
+    #[test]
+    fn random_bytes_reduce() {
+        assert_eq!(F17::from_random_bytes(&[18; 1]), F17::from_i128(1));
+        let bytes = [0xff; 16];
+        assert_eq!(
+            F17::from_random_bytes(&bytes).value,
+            (u128::MAX % 17) as i128
+        );
+    }
+
+    #[test]
+    fn prime_factors_by_trial_division() {
+        assert_eq!(prime_factors(12), vec![2, 3]);
+        assert_eq!(prime_factors(97), vec![97]);
+        assert_eq!(prime_factors(DEFAULT_FIELD_SIZE as u64 - 1), vec![2, 3]);
+        assert_eq!(
+            prime_factors(2 * 3 * 5 * 7 * 7 * 101),
+            vec![2, 3, 5, 7, 101]
+        );
+    }
+
+    #[test]
+    fn miller_rabin() {
+        let small: Vec<u64> = (0..50).filter(|&n| is_prime(n)).collect();
+        assert_eq!(
+            small,
+            vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47]
+        );
+        let default_prime = DEFAULT_FIELD_SIZE as u64;
+        assert!(is_prime(default_prime));
+        assert!(is_prime((1 << 61) - 1));
+        assert!(is_prime(0xffff_ffff_0000_0001));
+        assert!(is_prime(u64::MAX - 58));
+        // Carmichael number and a strong pseudoprime to bases 2, 3, 5, 7
+        assert!(!is_prime(561));
+        assert!(!is_prime(3_215_031_751));
+        assert!(!is_prime(default_prime * 97));
+        assert!(!is_prime(u64::MAX));
+    }
+
+    #[test]
+    fn two_adicity() {
+        assert_eq!(F::TWO_ADICITY, 30);
+        assert_eq!(F17::TWO_ADICITY, 4);
+        assert_eq!(F7::TWO_ADICITY, 1);
+    }
+
+    #[test]
+    fn multiplicative_generator() {
+        // 3 generates the 16 non-zero elements mod 17, 2 only has order 8
+        assert_eq!(F17::multiplicative_generator(), F17::from_i128(3));
+
+        let g = F::multiplicative_generator();
+        let one = F::ONE;
+        let group_order = F::CHARACTERISTIC - 1;
+        assert_eq!(g, FiniteFieldElement::new(5));
+        assert_ne!(g.pow(group_order / 2), one);
+        assert_ne!(g.pow(group_order / 3), one);
+    }
+
+    #[test]
+    fn primitive_root_of_unity() {
+        for n in [1, 2, 8, 1 << 30, 3, 3 << 10] {
+            let root = F::primitive_root_of_unity(n).unwrap();
+            assert_eq!(root.pow(n as u64), F::ONE);
+            // Primitive: no smaller power of two (or third) gives 1
+            if n % 2 == 0 {
+                assert_ne!(root.pow((n / 2) as u64), F::ONE);
+            }
+            if n % 3 == 0 {
+                assert_ne!(root.pow((n / 3) as u64), F::ONE);
+            }
+        }
+
+        assert_eq!(F::primitive_root_of_unity(0), Err(FieldError::ZeroOrder));
+        assert_eq!(
+            F::primitive_root_of_unity(1 << 31),
+            Err(FieldError::OrderDoesNotDivideGroupOrder {
+                order: 1 << 31,
+                group_order: F::CHARACTERISTIC - 1
+            })
+        );
+        assert!(F::primitive_root_of_unity(5).is_err());
     }
 
     /// A silly function to shorten the test lines
-    fn create(val: i128, field: FiniteField) -> FiniteFieldElement {
-        FiniteFieldElement::new_fielded(val, field)
+    fn create(val: i128) -> F5 {
+        F5::from_i128(val)
     }
 }
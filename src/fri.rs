@@ -1,115 +1,570 @@
+use crate::evaluation_domain::EvaluationDomain;
+use crate::fiat_shamir::Transcript;
+use crate::field::{batch_inverse_skipping_zeros, Field};
 use crate::finite_field::FiniteFieldElement;
+use crate::hashing::{Hasher, ToyHasher};
+use crate::merkle_tree::{MerkleProof, MerkleTree};
+use crate::polynomial::interpolate::lagrange_interpolation;
 
-/// Minimal FRI-style folding over evaluations on a coset of size 2^k.
+/// FRI folding over evaluations on a multiplicative domain of size 2^k.
 ///
-/// Given a vector of evaluations `values` of length N (N must be even), and a
-/// folding challenge `beta`, returns a new vector of length N/2 defined by:
-///    new[i] = values[i] + beta * values[i + N/2]
-/// This is a common educational simplification of the FRI folding step.
-pub fn fold_once(
-    values: &[FiniteFieldElement],
-    beta: FiniteFieldElement,
-) -> Vec<FiniteFieldElement> {
+/// Write f(x) = f_even(x^2) + x * f_odd(x^2). The domain has the x -> x^2
+/// structure: the point at i + N/2 is the negation of the point x at i, so from
+/// f(x) and f(-x) we get
+///    f_even(x^2) = (f(x) + f(-x)) / 2
+///    f_odd(x^2)  = (f(x) - f(-x)) / (2x)
+/// and return new[i] = f_even(x^2) + beta * f_odd(x^2), the evaluations of a
+/// polynomial of half the degree on `domain.square()`.
+pub fn fold_once<F: Field>(values: &[F], domain: &EvaluationDomain<F>, beta: F) -> Vec<F> {
     assert!(!values.is_empty(), "values must not be empty");
-    assert!(values.len() % 2 == 0, "values length must be even");
+    assert!(values.len().is_multiple_of(2), "values length must be even");
+    assert_eq!(values.len(), domain.size(), "one value per domain point");
 
+    // Invert the x of every pair at once; zero (only in linear domains) stays zero
     let half = values.len() / 2;
-    let mut out = Vec::with_capacity(half);
-    for i in 0..half {
-        let a = values[i];
-        let b = values[i + half];
-        out.push(a.add(beta.multiply(b)));
-    }
-    out
+    let mut x_invs: Vec<F> = (0..half).map(|i| domain.element(i)).collect();
+    batch_inverse_skipping_zeros(&mut x_invs);
+    let two_inv = F::from_u64(2).inverse();
+
+    (0..half)
+        .map(|i| fold_with_inverses(values[i], values[i + half], x_invs[i], two_inv, beta))
+        .collect()
+}
+
+/// Fold f(x) and f(-x) into f_even(x^2) + beta * f_odd(x^2)
+pub fn fold_pair<F: Field>(fx: F, f_neg_x: F, x: F, beta: F) -> F {
+    fold_with_inverses(fx, f_neg_x, x.inverse(), F::from_u64(2).inverse(), beta)
+}
+
+/// `fold_pair` with 1/x and 1/2 already computed
+fn fold_with_inverses<F: Field>(fx: F, f_neg_x: F, x_inv: F, two_inv: F, beta: F) -> F {
+    let even = fx.add(f_neg_x).multiply(two_inv);
+    let odd = fx.subtract(f_neg_x).multiply(two_inv).multiply(x_inv);
+    even.add(beta.multiply(odd))
 }
 
 /// Repeatedly folds until length <= target_len (power of two recommended).
 /// Panics if target_len is 0 or not a divisor of the initial length by a power of two.
-pub fn fold_until(
-    values: &[FiniteFieldElement],
-    betas: &[FiniteFieldElement],
+pub fn fold_until<F: Field>(
+    values: &[F],
+    domain: &EvaluationDomain<F>,
+    betas: &[F],
     target_len: usize,
-) -> Vec<FiniteFieldElement> {
+) -> Vec<F> {
     assert!(target_len > 0, "target_len must be > 0");
     assert!(
         values.len() >= target_len,
         "target_len must be <= initial length"
     );
     assert!(
-        values.len() % target_len == 0,
+        values.len().is_multiple_of(target_len),
         "target_len must divide initial length"
     );
 
     let mut cur = values.to_vec();
+    let mut cur_domain = domain.clone();
     let mut beta_iter = betas.iter();
     while cur.len() > target_len {
         let beta = *beta_iter
             .next()
             .expect("not enough betas to fold to target_len");
-        cur = fold_once(&cur, beta);
+        cur = fold_once(&cur, &cur_domain, beta);
+        cur_domain = cur_domain.square();
     }
     cur
 }
 
+/// Opening of one folding pair in a committed layer: the values at positions
+/// i and i + N/2 together with their Merkle proofs
+#[derive(Debug, Clone)]
+pub struct FriDecommitment<F: Field = FiniteFieldElement, H: Hasher = ToyHasher> {
+    pub values: [F; 2],
+    pub merkle_proofs: [MerkleProof<H::Digest>; 2],
+}
+
+/// One FRI query: the folding pair containing `index` opened in every committed layer
+#[derive(Debug, Clone)]
+pub struct FriQuery<F: Field = FiniteFieldElement, H: Hasher = ToyHasher> {
+    /// Queried position in the first layer
+    pub index: usize,
+    /// One opening per committed layer, first layer first
+    pub layers: Vec<FriDecommitment<F, H>>,
+}
+
+impl<F: Field, H: Hasher> FriQuery<F, H> {
+    /// Value of the first layer at `index`, given the first layer length
+    pub fn first_layer_value(&self, layer_len: usize) -> Option<F> {
+        let opening = self.layers.first()?;
+        Some(opening.values[usize::from(self.index >= layer_len / 2)])
+    }
+}
+
+/// FRI proof: one Merkle root per folded layer, the final layer in the clear
+/// and the answers to the queries, all committed with `H`
+#[derive(Debug, Clone)]
+pub struct FriProof<F: Field = FiniteFieldElement, H: Hasher = ToyHasher> {
+    pub layer_commitments: Vec<H::Digest>,
+    pub final_layer: Vec<F>,
+    pub queries: Vec<FriQuery<F, H>>,
+}
+
+/// Prover side of FRI: all committed layers, kept around to answer queries
+pub struct FriProver<F: Field = FiniteFieldElement, H: Hasher = ToyHasher> {
+    layers: Vec<Vec<F>>,
+    trees: Vec<MerkleTree<H>>,
+    final_layer: Vec<F>,
+    betas: Vec<F>,
+}
+
+impl<F: Field, H: Hasher> FriProver<F, H> {
+    /// Commit phase: commit to each layer with a Merkle tree, absorb its root and
+    /// draw the folding challenge for that layer from the transcript. Folding stops
+    /// once the layer has at most `final_layer_size` values; the final layer is
+    /// absorbed as a whole. `values` are the evaluations over `domain`.
+    pub fn commit(
+        values: &[F],
+        domain: &EvaluationDomain<F>,
+        final_layer_size: usize,
+        transcript: &mut Transcript<H>,
+    ) -> Self {
+        assert!(
+            values.len().is_power_of_two(),
+            "FRI layer length must be a power of two"
+        );
+        assert!(
+            final_layer_size > 0 && values.len() > final_layer_size,
+            "FRI needs at least one folding round"
+        );
+
+        let mut layers = Vec::new();
+        let mut trees = Vec::new();
+        let mut betas = Vec::new();
+        let mut cur = values.to_vec();
+        let mut cur_domain = domain.clone();
+        while cur.len() > final_layer_size {
+            let mut tree = MerkleTree::new();
+            tree.build(&cur);
+            transcript.append_root(b"fri_layer", tree.root().unwrap());
+            let beta = transcript.challenge(b"fri_beta");
+
+            let next = fold_once(&cur, &cur_domain, beta);
+            cur_domain = cur_domain.square();
+            layers.push(cur);
+            trees.push(tree);
+            betas.push(beta);
+            cur = next;
+        }
+        absorb_final_layer(&cur, transcript);
+
+        FriProver {
+            layers,
+            trees,
+            final_layer: cur,
+            betas,
+        }
+    }
+
+    /// Merkle roots of the committed layers
+    pub fn layer_commitments(&self) -> Vec<H::Digest> {
+        self.trees.iter().map(|t| t.root().unwrap()).collect()
+    }
+
+    /// Folding challenges drawn during the commit phase
+    pub fn betas(&self) -> &[F] {
+        &self.betas
+    }
+
+    /// Query phase: open the folding pair of every queried index in every layer
+    pub fn prove(&self, indices: &[usize]) -> FriProof<F, H> {
+        let queries = indices
+            .iter()
+            .map(|&index| {
+                let mut idx = index;
+                let mut openings = Vec::with_capacity(self.layers.len());
+                for (layer, tree) in self.layers.iter().zip(self.trees.iter()) {
+                    let half = layer.len() / 2;
+                    let pos = idx % half;
+                    openings.push(FriDecommitment {
+                        values: [layer[pos], layer[pos + half]],
+                        merkle_proofs: [
+                            tree.get_merkle_proof(pos).unwrap(),
+                            tree.get_merkle_proof(pos + half).unwrap(),
+                        ],
+                    });
+                    idx = pos;
+                }
+                FriQuery {
+                    index,
+                    layers: openings,
+                }
+            })
+            .collect();
+
+        FriProof {
+            layer_commitments: self.layer_commitments(),
+            final_layer: self.final_layer.clone(),
+            queries,
+        }
+    }
+}
+
+fn absorb_final_layer<F: Field, H: Hasher>(final_layer: &[F], transcript: &mut Transcript<H>) {
+    transcript.append_field_elements(b"fri_final_layer", final_layer);
+}
+
+/// Replay the commit phase on the verifier side: absorb each layer root, draw its
+/// beta and absorb the final layer, exactly as `FriProver::commit` does
+pub fn derive_betas<F: Field, H: Hasher>(
+    proof: &FriProof<F, H>,
+    transcript: &mut Transcript<H>,
+) -> Vec<F> {
+    if proof.final_layer.is_empty() {
+        return Vec::new();
+    }
+    let betas = proof
+        .layer_commitments
+        .iter()
+        .map(|&root| {
+            transcript.append_root(b"fri_layer", root);
+            transcript.challenge(b"fri_beta")
+        })
+        .collect();
+    absorb_final_layer(&proof.final_layer, transcript);
+    betas
+}
+
+/// Verify a FRI proof for a first layer of evaluations over `domain` of a
+/// polynomial with degree below `degree_bound`: every opened pair must be in its
+/// layer's Merkle tree, folding it with that layer's beta must give the value
+/// opened in the next layer (or the final layer), and the final layer must be the
+/// evaluations of a polynomial with degree below the folded bound.
+pub fn verify_fri<F: Field, H: Hasher>(
+    proof: &FriProof<F, H>,
+    betas: &[F],
+    domain: &EvaluationDomain<F>,
+    degree_bound: usize,
+    final_layer_size: usize,
+    indices: &[usize],
+) -> bool {
+    let domain_len = domain.size();
+    // The prover folds while the layer is larger than `final_layer_size`
+    let mut expected_layers = 0;
+    let mut len = domain_len;
+    while len > final_layer_size.max(1) && len.is_multiple_of(2) {
+        len /= 2;
+        expected_layers += 1;
+    }
+    let num_layers = proof.layer_commitments.len();
+    if num_layers == 0
+        || num_layers != expected_layers
+        || betas.len() != num_layers
+        || proof.final_layer.len() != len
+        || proof.queries.len() != indices.len()
+    {
+        return false;
+    }
+
+    // Domain of every committed layer, and of the final layer
+    let mut domains = vec![domain.clone()];
+    for _ in 0..num_layers {
+        let next = domains.last().unwrap().square();
+        domains.push(next);
+    }
+
+    for (query, &index) in proof.queries.iter().zip(indices.iter()) {
+        if query.index != index || index >= domain_len || query.layers.len() != num_layers {
+            return false;
+        }
+
+        let mut idx = index;
+        let mut expected: Option<F> = None;
+        for (l, opening) in query.layers.iter().enumerate() {
+            let half = domains[l].size() / 2;
+            let root = proof.layer_commitments[l];
+            let positions = [idx % half, idx % half + half];
+            for ((value, merkle_proof), position) in opening
+                .values
+                .iter()
+                .zip(opening.merkle_proofs.iter())
+                .zip(positions)
+            {
+                let leaf = H::hash_leaf(core::slice::from_ref(value));
+                if !MerkleTree::<H>::verify(root, position, leaf, merkle_proof) {
+                    return false;
+                }
+            }
+
+            // The value folded in the previous layer must be the one opened here
+            if let Some(e) = expected {
+                if opening.values[usize::from(idx >= half)] != e {
+                    return false;
+                }
+            }
+            idx %= half;
+            expected = Some(fold_pair(
+                opening.values[0],
+                opening.values[1],
+                domains[l].element(idx),
+                betas[l],
+            ));
+        }
+
+        if expected != Some(proof.final_layer[idx]) {
+            return false;
+        }
+    }
+
+    // Each fold halves the degree
+    let final_degree_bound = degree_bound.div_ceil(1 << num_layers);
+    let final_domain = &domains[num_layers];
+    let points: Vec<(F, F)> = final_domain
+        .points
+        .iter()
+        .copied()
+        .zip(proof.final_layer.iter().copied())
+        .collect();
+    lagrange_interpolation(&points).degree() < final_degree_bound
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{constants::DEFAULT_FIELD_SIZE, finite_field::FiniteField};
+    use crate::extension_field::ExtensionField;
+    use crate::polynomial::polynomial::Polynomial;
 
     use super::*;
 
+    type F = FiniteFieldElement;
+
+    fn transcript() -> Transcript {
+        Transcript::new(b"fri-test")
+    }
+
+    fn subgroup(n: usize) -> EvaluationDomain {
+        EvaluationDomain::new_subgroup(n)
+    }
+
+    fn evaluate_on(poly: &Polynomial, domain: &EvaluationDomain) -> Vec<F> {
+        domain.points.iter().map(|&x| poly.evaluate(x)).collect()
+    }
+
+    fn interpolate_on(values: &[F], domain: &EvaluationDomain) -> Polynomial {
+        let points: Vec<(F, F)> = domain
+            .points
+            .iter()
+            .copied()
+            .zip(values.iter().copied())
+            .collect();
+        lagrange_interpolation(&points)
+    }
+
     #[test]
     fn fold_once_halves_length_and_is_deterministic() {
-        let field = FiniteField::new(DEFAULT_FIELD_SIZE);
+        let domain = subgroup(8);
         // Build 8 sample values: 0..7
-        let values: Vec<FiniteFieldElement> = (0..8)
-            .map(|i| FiniteFieldElement::new_fielded(i as i128, field))
-            .collect();
-        let beta = FiniteFieldElement::new_fielded(3, field);
+        let values: Vec<F> = (0..8).map(F::new).collect();
+        let beta = F::new(3);
 
-        let folded = fold_once(&values, beta);
+        let folded = fold_once(&values, &domain, beta);
         assert_eq!(folded.len(), 4);
 
-        // Manual expectation: out[i] = v[i] + beta * v[i+4]
+        // Manual expectation: out[i] = (v[i] + v[i+4]) / 2 + beta * (v[i] - v[i+4]) / (2x)
+        let two = F::new(2);
         for i in 0..4 {
-            let expected = values[i].add(beta.multiply(values[i + 4]));
-            assert_eq!(folded[i].value, expected.value);
-            assert_eq!(folded[i].field.prime, expected.field.prime);
+            let x = domain.element(i);
+            assert_eq!(x.negate(), domain.element(i + 4));
+            let even = values[i].add(values[i + 4]).multiply(two.inverse());
+            let odd = values[i]
+                .subtract(values[i + 4])
+                .multiply(two.multiply(x).inverse());
+            let expected = even.add(beta.multiply(odd));
+            assert_eq!(folded[i], expected);
         }
     }
 
     #[test]
     fn fold_until_reduces_to_target_len() {
-        let field = FiniteField::new(DEFAULT_FIELD_SIZE);
-        let values: Vec<FiniteFieldElement> = (0..16)
-            .map(|i| FiniteFieldElement::new_fielded((i as i128) * 7 + 1, field))
-            .collect();
+        let domain = subgroup(16);
+        let values: Vec<F> = (0..16).map(|i| F::new(i * 7 + 1)).collect();
 
         // Two rounds needed from 16 -> 4
-        let betas = vec![
-            FiniteFieldElement::new_fielded(5, field),
-            FiniteFieldElement::new_fielded(9, field),
-        ];
+        let betas = vec![F::new(5), F::new(9)];
 
-        let out = fold_until(&values, &betas, 4);
+        let out = fold_until(&values, &domain, &betas, 4);
         assert_eq!(out.len(), 4);
 
         // Check consistency with two fold_once rounds
-        let after_one = fold_once(&values, betas[0]);
-        let after_two = fold_once(&after_one, betas[1]);
-        for (a, b) in out.iter().zip(after_two.iter()) {
-            assert_eq!(a.value, b.value);
-            assert_eq!(a.field.prime, b.field.prime);
+        let after_one = fold_once(&values, &domain, betas[0]);
+        let after_two = fold_once(&after_one, &domain.square(), betas[1]);
+        assert_eq!(out, after_two);
+    }
+
+    #[test]
+    fn fold_halves_degree() {
+        let domain = subgroup(32);
+        let coefficients = vec![7, 3, 11, 2, 9, 4, 1, 6];
+        let poly = Polynomial::new(coefficients.clone());
+        assert_eq!(poly.degree(), 7);
+        let beta = F::new(10);
+
+        let folded = fold_once(&evaluate_on(&poly, &domain), &domain, beta);
+        let folded_domain = domain.square();
+        let folded_poly = interpolate_on(&folded, &folded_domain);
+        assert_eq!(folded_poly.degree(), 3);
+
+        // The folded polynomial is f_even + beta * f_odd
+        for (i, c) in folded_poly.coefficients.iter().take(4).enumerate() {
+            let expected =
+                F::new(coefficients[2 * i]).add(beta.multiply(F::new(coefficients[2 * i + 1])));
+            assert_eq!(*c, expected);
+        }
+
+        let twice = fold_once(&folded, &folded_domain, beta);
+        assert_eq!(interpolate_on(&twice, &folded_domain.square()).degree(), 1);
+    }
+
+    /// Evaluations of a degree 7 polynomial over a domain of 32 points
+    fn commit_values() -> (Vec<F>, EvaluationDomain) {
+        let domain = subgroup(32);
+        let poly = Polynomial::new(vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        (evaluate_on(&poly, &domain), domain)
+    }
+
+    #[test]
+    fn fri_proof_verifies_and_betas_match() {
+        let (values, domain) = commit_values();
+        let indices = [0, 5, 17, 31];
+
+        let mut prover_transcript = transcript();
+        let prover = FriProver::commit(&values, &domain, 4, &mut prover_transcript);
+        let proof = prover.prove(&indices);
+        assert_eq!(proof.layer_commitments.len(), 3);
+        assert_eq!(proof.final_layer.len(), 4);
+
+        let mut verifier_transcript = transcript();
+        let betas = derive_betas(&proof, &mut verifier_transcript);
+        assert_eq!(betas, prover.betas());
+        // Both transcripts end in the same state
+        assert_eq!(
+            prover_transcript.challenge::<F>(b"next"),
+            verifier_transcript.challenge::<F>(b"next")
+        );
+
+        assert!(verify_fri(&proof, &betas, &domain, 8, 4, &indices));
+        assert_eq!(proof.queries[2].first_layer_value(32), Some(values[17]));
+    }
+
+    #[test]
+    fn fri_rejects_tampered_openings() {
+        let (values, domain) = commit_values();
+        let indices = [3, 20];
+
+        let prover = FriProver::commit(&values, &domain, 4, &mut transcript());
+        let proof = prover.prove(&indices);
+        let betas = derive_betas(&proof, &mut transcript());
+
+        // Value not in the committed layer
+        let mut bad = proof.clone();
+        let v = &mut bad.queries[0].layers[1].values[0];
+        *v = v.add(F::ONE);
+        assert!(!verify_fri(&bad, &betas, &domain, 8, 4, &indices));
+
+        // Final layer inconsistent with the folds
+        let mut bad = proof.clone();
+        for v in bad.final_layer.iter_mut() {
+            *v = v.add(F::ONE);
+        }
+        assert!(!verify_fri(&bad, &betas, &domain, 8, 4, &indices));
+
+        // Answers for different positions than the ones asked
+        assert!(!verify_fri(&proof, &betas, &domain, 8, 4, &[4, 20]));
+        assert!(verify_fri(&proof, &betas, &domain, 8, 4, &indices));
+    }
+
+    #[test]
+    fn fri_rejects_a_wrong_number_of_layers() {
+        let (values, domain) = commit_values();
+        let indices = [3, 20];
+        let proof = FriProver::commit(&values, &domain, 4, &mut transcript()).prove(&indices);
+
+        // One layer too few, and more layers than the domain can be halved
+        for num_layers in [2, 64, 100] {
+            let mut forged = proof.clone();
+            forged
+                .layer_commitments
+                .resize(num_layers, proof.layer_commitments[0]);
+            let betas = derive_betas(&forged, &mut transcript());
+            assert!(!verify_fri(&forged, &betas, &domain, 8, 4, &indices));
         }
     }
 
+    #[test]
+    fn fri_rejects_high_degree() {
+        let domain = subgroup(32);
+        let values: Vec<F> = (0..32).map(|i| F::new(i * i + 3)).collect();
+        let indices = [1, 2, 30];
+
+        let prover = FriProver::commit(&values, &domain, 4, &mut transcript());
+        let proof = prover.prove(&indices);
+        let betas = derive_betas(&proof, &mut transcript());
+
+        // Consistent folds, but the final layer is not constant
+        assert!(!verify_fri(&proof, &betas, &domain, 8, 4, &indices));
+        assert!(verify_fri(&proof, &betas, &domain, 32, 4, &indices));
+
+        // Degree 7 folded twice has degree 1, which is not below 4 / 4
+        let (values, domain) = commit_values();
+        let prover = FriProver::commit(&values, &domain, 8, &mut transcript());
+        let proof = prover.prove(&indices);
+        let betas = derive_betas(&proof, &mut transcript());
+        assert!(!verify_fri(&proof, &betas, &domain, 4, 8, &indices));
+        assert!(verify_fri(&proof, &betas, &domain, 8, 8, &indices));
+    }
+
+    #[test]
+    fn fri_works_in_a_small_field() {
+        // p = 97 has a subgroup of order 32
+        type F97 = FiniteFieldElement<97>;
+        let domain = EvaluationDomain::<F97>::new_subgroup(32);
+        let poly = Polynomial::new_ff((1..=4).map(F97::from_i128).collect());
+        let values: Vec<F97> = domain.points.iter().map(|&x| poly.evaluate(x)).collect();
+        let indices = [2, 9];
+
+        let prover = FriProver::commit(&values, &domain, 4, &mut transcript());
+        let proof = prover.prove(&indices);
+        let betas = derive_betas(&proof, &mut transcript());
+        assert!(verify_fri(&proof, &betas, &domain, 4, 4, &indices));
+    }
+
+    #[test]
+    fn fri_works_over_an_extension() {
+        // Values and folding challenges in the quadratic extension, domain in the base field
+        type E = ExtensionField<2>;
+        let domain = EvaluationDomain::<E>::new_coset(32, E::multiplicative_generator());
+        let poly = Polynomial::new(vec![1, 2, 3, 4]);
+        let shift = E::new([F::new(7), F::new(11)]);
+        let values: Vec<E> = domain
+            .points
+            .iter()
+            .map(|&x| poly.evaluate_extension(x).multiply(shift))
+            .collect();
+        let indices = [2, 9];
+
+        let prover = FriProver::commit(&values, &domain, 4, &mut transcript());
+        assert!(prover
+            .betas()
+            .iter()
+            .all(|b| !b.coefficients()[1].is_zero()));
+        let proof = prover.prove(&indices);
+        let betas = derive_betas(&proof, &mut transcript());
+        assert!(verify_fri(&proof, &betas, &domain, 4, 4, &indices));
+    }
+
     #[test]
     #[should_panic]
     fn fold_once_panics_on_odd_length() {
-        let field = FiniteField::new(DEFAULT_FIELD_SIZE);
-        let values: Vec<FiniteFieldElement> = (0..5)
-            .map(|i| FiniteFieldElement::new_fielded(i as i128, field))
-            .collect();
-        let beta = FiniteFieldElement::new_fielded(2, field);
-        let _ = fold_once(&values, beta);
+        let domain = EvaluationDomain::new_linear(5);
+        let values: Vec<F> = (0..5).map(F::new).collect();
+        let beta = F::new(2);
+        let _ = fold_once(&values, &domain, beta);
     }
 }
@@ -0,0 +1,274 @@
+use core::fmt;
+
+use crate::field::Field;
+
+/// The Goldilocks prime p = 2^64 - 2^32 + 1
+pub const MODULUS: u64 = 0xffff_ffff_0000_0001;
+
+/// 2^64 mod p = 2^32 - 1
+const EPSILON: u64 = 0xffff_ffff;
+
+/// Element of the Goldilocks field, stored canonically in [0, p). The shape of p
+/// gives a reduction without division: 2^64 = 2^32 - 1 and 2^96 = -1 (mod p).
+/// p - 1 = 2^32 · 3 · 5 · 17 · 257 · 65537, so there are NTT domains up to 2^32.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Goldilocks {
+    pub value: u64,
+}
+
+impl Goldilocks {
+    /// The element `value` mod p
+    pub const fn new(value: u64) -> Self {
+        // Every u64 is below 2p, so one subtraction reduces it
+        Goldilocks {
+            value: if value >= MODULUS {
+                value - MODULUS
+            } else {
+                value
+            },
+        }
+    }
+}
+
+/// Reduce a 128-bit value mod p. With x = x_lo + 2^64 · x_hi_lo + 2^96 · x_hi_hi,
+/// x = x_lo - x_hi_hi + (2^32 - 1) · x_hi_lo (mod p).
+fn reduce128(x: u128) -> u64 {
+    let x_lo = x as u64;
+    let x_hi = (x >> 64) as u64;
+    let x_hi_hi = x_hi >> 32;
+    let x_hi_lo = x_hi & EPSILON;
+
+    // x_lo - x_hi_hi; on a borrow 2^64 was added, which is EPSILON too many
+    // (no underflow: the wrapped value is at least 2^64 - 2^32)
+    let (mut t0, borrow) = x_lo.overflowing_sub(x_hi_hi);
+    if borrow {
+        t0 -= EPSILON;
+    }
+    // (2^32 - 1)^2 fits in a u64
+    let t1 = x_hi_lo * EPSILON;
+    // On a carry 2^64 was dropped: add back EPSILON (no overflow, the sum is small)
+    let (t2, carry) = t0.overflowing_add(t1);
+    let t2 = if carry { t2 + EPSILON } else { t2 };
+    Goldilocks::new(t2).value
+}
+
+impl fmt::Display for Goldilocks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl Field for Goldilocks {
+    const ZERO: Self = Goldilocks { value: 0 };
+    const ONE: Self = Goldilocks { value: 1 };
+    const CHARACTERISTIC: u64 = MODULUS;
+    const TWO_ADICITY: u32 = 32;
+    const ENCODED_SIZE: usize = 8;
+
+    fn from_u64(value: u64) -> Self {
+        Goldilocks::new(value)
+    }
+
+    fn add(&self, other: Self) -> Self {
+        // a + b < 2p; on overflow 2^64 = EPSILON was dropped
+        let (sum, carry) = self.value.overflowing_add(other.value);
+        let sum = if carry { sum + EPSILON } else { sum };
+        Goldilocks::new(sum)
+    }
+
+    fn subtract(&self, other: Self) -> Self {
+        // On a borrow 2^64 was added; take EPSILON away to add p instead
+        let (diff, borrow) = self.value.overflowing_sub(other.value);
+        let diff = if borrow { diff - EPSILON } else { diff };
+        Goldilocks { value: diff }
+    }
+
+    fn multiply(&self, other: Self) -> Self {
+        Goldilocks {
+            value: reduce128(self.value as u128 * other.value as u128),
+        }
+    }
+
+    fn negate(&self) -> Self {
+        if self.value == 0 {
+            *self
+        } else {
+            Goldilocks {
+                value: MODULUS - self.value,
+            }
+        }
+    }
+
+    fn inverse(&self) -> Self {
+        // Fermat's little theorem: a^(p-2) mod p
+        self.pow(MODULUS - 2)
+    }
+
+    /// 7 generates the multiplicative group
+    fn multiplicative_generator() -> Self {
+        Goldilocks { value: 7 }
+    }
+
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        self.value.to_le_bytes().to_vec()
+    }
+
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self> {
+        let value = u64::from_le_bytes(bytes.try_into().ok()?);
+        (value < MODULUS).then_some(Goldilocks { value })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FieldError;
+
+    const P: u128 = MODULUS as u128;
+
+    /// Values around every boundary the reduction cares about
+    fn edge_values() -> Vec<u64> {
+        let mut values = vec![
+            0,
+            1,
+            2,
+            EPSILON - 1,
+            EPSILON,
+            EPSILON + 1,
+            1 << 32,
+            1 << 63,
+            MODULUS / 2,
+            MODULUS / 2 + 1,
+            MODULUS - EPSILON,
+            MODULUS - 2,
+            MODULUS - 1,
+        ];
+        // A spread of other values
+        let mut x: u64 = 0x9e37_79b9_7f4a_7c15;
+        for _ in 0..32 {
+            x = x
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            values.push(x % MODULUS);
+        }
+        values
+    }
+
+    fn g(value: u64) -> Goldilocks {
+        Goldilocks::new(value)
+    }
+
+    #[test]
+    fn new_reduces_the_whole_u64_range() {
+        assert_eq!(g(MODULUS).value, 0);
+        assert_eq!(g(MODULUS + 1).value, 1);
+        assert_eq!(g(u64::MAX).value, EPSILON - 1);
+        assert_eq!(g(MODULUS - 1).value, MODULUS - 1);
+        assert_eq!(Goldilocks::from_i128(-1).value, MODULUS - 1);
+        assert_eq!(
+            Goldilocks::from_i128(i128::MAX).value,
+            (i128::MAX as u128 % P) as u64
+        );
+    }
+
+    #[test]
+    fn reduce128_matches_u128_remainder() {
+        let mut inputs = vec![0, 1, P - 1, P, P + 1, 2 * P, u64::MAX as u128, u128::MAX];
+        inputs.extend([
+            1u128 << 64,
+            1 << 96,
+            (1 << 96) - 1,
+            (1 << 96) + 1,
+            (P - 1) * (P - 1),
+        ]);
+        for a in edge_values() {
+            for b in [0, 1, EPSILON, 1 << 63, MODULUS - 1, a] {
+                inputs.push(a as u128 * b as u128);
+            }
+        }
+        for x in inputs {
+            assert_eq!(reduce128(x) as u128, x % P, "reducing {}", x);
+        }
+    }
+
+    #[test]
+    fn arithmetic_matches_u128_reference() {
+        let values = edge_values();
+        for &a in &values {
+            for &b in &values {
+                let (x, y) = (g(a), g(b));
+                let (a, b) = (a as u128, b as u128);
+                assert_eq!(x.add(y).value as u128, (a + b) % P, "{} + {}", a, b);
+                assert_eq!(
+                    x.subtract(y).value as u128,
+                    (a + P - b) % P,
+                    "{} - {}",
+                    a,
+                    b
+                );
+                assert_eq!(x.multiply(y).value as u128, a * b % P, "{} * {}", a, b);
+            }
+            assert_eq!(g(a).negate().add(g(a)), Goldilocks::ZERO);
+        }
+    }
+
+    #[test]
+    fn results_are_canonical() {
+        let values = edge_values();
+        for &a in &values {
+            for &b in &values {
+                let (x, y) = (g(a), g(b));
+                for r in [x.add(y), x.subtract(y), x.multiply(y), x.negate()] {
+                    assert!(r.value < MODULUS);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn inverse() {
+        for a in edge_values().into_iter().filter(|&a| a != 0) {
+            assert_eq!(g(a).multiply(g(a).inverse()), Goldilocks::ONE, "1 / {}", a);
+        }
+        assert_eq!(Goldilocks::ZERO.inverse(), Goldilocks::ZERO);
+        // -1 is its own inverse
+        assert_eq!(g(MODULUS - 1).inverse(), g(MODULUS - 1));
+    }
+
+    #[test]
+    fn roots_of_unity_up_to_two_to_the_32() {
+        let generator = Goldilocks::multiplicative_generator();
+        assert_eq!(generator.pow(MODULUS - 1), Goldilocks::ONE);
+        for q in [2, 3, 5, 17, 257, 65537] {
+            assert_ne!(generator.pow((MODULUS - 1) / q), Goldilocks::ONE);
+        }
+
+        let root = Goldilocks::primitive_root_of_unity(1 << 32).unwrap();
+        assert_eq!(root.pow(1 << 32), Goldilocks::ONE);
+        assert_eq!(root.pow(1 << 31), g(MODULUS - 1));
+        // 2^32 · 3 divides p - 1, but 2^33 does not
+        assert!(Goldilocks::primitive_root_of_unity(3 << 32).is_ok());
+        assert_eq!(
+            Goldilocks::primitive_root_of_unity(1 << 33),
+            Err(FieldError::OrderDoesNotDivideGroupOrder {
+                order: 1 << 33,
+                group_order: MODULUS - 1
+            })
+        );
+        assert_eq!(Goldilocks::TWO_ADICITY, (MODULUS - 1).trailing_zeros());
+    }
+
+    #[test]
+    fn canonical_bytes() {
+        for a in edge_values() {
+            let bytes = g(a).to_canonical_bytes();
+            assert_eq!(bytes.len(), Goldilocks::ENCODED_SIZE);
+            assert_eq!(Goldilocks::from_canonical_bytes(&bytes), Some(g(a)));
+        }
+        // p itself and everything above is not canonical
+        for v in [MODULUS, MODULUS + 1, u64::MAX] {
+            assert_eq!(Goldilocks::from_canonical_bytes(&v.to_le_bytes()), None);
+        }
+        assert_eq!(Goldilocks::from_canonical_bytes(&[0; 7]), None);
+    }
+}
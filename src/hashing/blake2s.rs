@@ -0,0 +1,208 @@
+//! BLAKE2s (RFC 7693) with a 32-byte output, written out by hand.
+
+use crate::field::Field;
+
+use super::Hasher;
+
+/// Same initialization vector as SHA-256
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Message word permutation for each of the 10 rounds
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+const BLOCK_LEN: usize = 64;
+const OUT_LEN: usize = 32;
+
+/// Mixing function G on four words of the work vector
+fn mix(v: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize, x: u32, y: u32) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(12);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(8);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(7);
+}
+
+/// Compression function F: `counter` is the number of bytes hashed so far,
+/// including this block
+fn compress(h: &mut [u32; 8], block: &[u8; BLOCK_LEN], counter: u64, last: bool) {
+    let mut m = [0u32; 16];
+    for (i, word) in block.chunks_exact(4).enumerate() {
+        m[i] = u32::from_le_bytes(word.try_into().unwrap());
+    }
+
+    let mut v = [0u32; 16];
+    v[..8].copy_from_slice(h);
+    v[8..].copy_from_slice(&IV);
+    v[12] ^= counter as u32;
+    v[13] ^= (counter >> 32) as u32;
+    if last {
+        v[14] = !v[14];
+    }
+
+    for s in &SIGMA {
+        mix(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+        mix(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+        mix(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+        mix(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+        mix(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+        mix(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+        mix(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+        mix(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+    }
+
+    for i in 0..8 {
+        h[i] ^= v[i] ^ v[i + 8];
+    }
+}
+
+/// Keyed BLAKE2s-256 (MAC mode); an empty key gives the plain hash.
+/// Panics if the key is longer than 32 bytes.
+pub fn blake2s_keyed(key: &[u8], data: &[u8]) -> [u8; OUT_LEN] {
+    assert!(key.len() <= 32, "BLAKE2s keys are at most 32 bytes");
+
+    let mut h = IV;
+    // Parameter block: digest length, key length, fanout = depth = 1
+    h[0] ^= 0x0101_0000 ^ ((key.len() as u32) << 8) ^ OUT_LEN as u32;
+
+    // A key is hashed as a full first block
+    let mut input = Vec::with_capacity(BLOCK_LEN + data.len());
+    if !key.is_empty() {
+        input.extend_from_slice(key);
+        input.resize(BLOCK_LEN, 0);
+    }
+    input.extend_from_slice(data);
+
+    // Every block but the last is compressed as non-final; the last one may be
+    // partial (zero-padded) and is always present, even for empty input
+    let full_blocks = input.len().saturating_sub(1) / BLOCK_LEN;
+    for (i, block) in input.chunks_exact(BLOCK_LEN).take(full_blocks).enumerate() {
+        let counter = ((i + 1) * BLOCK_LEN) as u64;
+        compress(&mut h, block.try_into().unwrap(), counter, false);
+    }
+    let mut last = [0u8; BLOCK_LEN];
+    let rest = &input[full_blocks * BLOCK_LEN..];
+    last[..rest.len()].copy_from_slice(rest);
+    compress(&mut h, &last, input.len() as u64, true);
+
+    let mut out = [0u8; OUT_LEN];
+    for (chunk, word) in out.chunks_exact_mut(4).zip(h) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    out
+}
+
+/// BLAKE2s-256 digest of `data`
+pub fn blake2s(data: &[u8]) -> [u8; OUT_LEN] {
+    blake2s_keyed(&[], data)
+}
+
+/// BLAKE2s-256 as a Merkle / transcript hasher, with the same leaf and node
+/// encoding as `Sha256`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Blake2s;
+
+impl Hasher for Blake2s {
+    type Digest = [u8; 32];
+
+    const COLLISION_RESISTANCE: u32 = 128;
+    const DIGEST_SIZE: usize = 32;
+
+    fn hash_bytes(data: &[u8]) -> [u8; 32] {
+        blake2s(data)
+    }
+
+    fn hash_leaf<F: Field>(values: &[F]) -> [u8; 32] {
+        blake2s(&super::leaf_bytes(values))
+    }
+
+    fn hash_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        blake2s(&super::node_bytes(left, right))
+    }
+
+    fn digest_to_bytes(digest: &[u8; 32]) -> Vec<u8> {
+        digest.to_vec()
+    }
+
+    fn digest_from_bytes(bytes: &[u8]) -> Option<[u8; 32]> {
+        bytes.try_into().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashing::to_hex;
+
+    #[test]
+    fn rfc_7693_test_vectors() {
+        assert_eq!(
+            to_hex(&blake2s(b"abc")),
+            "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982"
+        );
+        assert_eq!(
+            to_hex(&blake2s(b"")),
+            "69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9"
+        );
+    }
+
+    #[test]
+    fn reference_keyed_test_vectors() {
+        // blake2s-kat.txt: key = 00..1f, input = 00..(n-1)
+        let key: Vec<u8> = (0..32).collect();
+        assert_eq!(
+            to_hex(&blake2s_keyed(&key, b"")),
+            "48a8997da407876b3d79c0d92325ad3b89cbb754d86ab71aee047ad345fd2c49"
+        );
+        let input: Vec<u8> = (0..255).collect();
+        assert_eq!(
+            to_hex(&blake2s_keyed(&key, &input)),
+            "3fb735061abc519dfe979e54c1ee5bfad0a9d858b3315bad34bde999efd724dd"
+        );
+    }
+
+    #[test]
+    fn block_boundaries() {
+        let cases = [
+            (
+                63,
+                "e57cb79487dd57902432b250733813bd96a84efce59f650fac26e6696aefafc3",
+            ),
+            (
+                64,
+                "56f34e8b96557e90c1f24b52d0c89d51086acf1b00f634cf1dde9233b8eaaa3e",
+            ),
+            (
+                65,
+                "1b53ee94aaf34e4b159d48de352c7f0661d0a40edff95a0b1639b4090e974472",
+            ),
+            (
+                128,
+                "1fa877de67259d19863a2a34bcc6962a2b25fcbf5cbecd7ede8f1fa36688a796",
+            ),
+            (
+                1000,
+                "1c067a5e746fb0f6734efac9a8cdb0e11061f0077f255184365c690115392501",
+            ),
+        ];
+        for (len, expected) in cases {
+            let data: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            assert_eq!(to_hex(&blake2s(&data)), expected, "length {}", len);
+        }
+    }
+}
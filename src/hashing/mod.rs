@@ -0,0 +1,279 @@
+use core::fmt::Debug;
+
+use crate::field::Field;
+
+pub mod blake2s;
+pub mod poseidon;
+pub mod sha256;
+
+pub use blake2s::Blake2s;
+pub use poseidon::Poseidon;
+pub use sha256::Sha256;
+
+/// Hash function used for Merkle commitments and the Fiat–Shamir transcript.
+/// Implementations are zero-sized markers; everything is an associated function.
+pub trait Hasher: Debug + Clone + Copy + Default {
+    /// Output of the hash; `Default` is used as the padding leaf
+    type Digest: Debug + Clone + Copy + Default + PartialEq + Eq;
+
+    /// Collision resistance in bits, at most half the digest size
+    const COLLISION_RESISTANCE: u32;
+
+    /// Length of `digest_to_bytes`
+    const DIGEST_SIZE: usize;
+
+    /// Hash arbitrary bytes (transcript input)
+    fn hash_bytes(data: &[u8]) -> Self::Digest;
+
+    /// Leaf digest of a row of field elements
+    fn hash_leaf<F: Field>(values: &[F]) -> Self::Digest;
+
+    /// Parent digest of two children; must be ordered (not commutative)
+    fn hash_node(left: &Self::Digest, right: &Self::Digest) -> Self::Digest;
+
+    /// Byte encoding of a digest, used to sample challenges from it and to
+    /// serialize commitments
+    fn digest_to_bytes(digest: &Self::Digest) -> Vec<u8>;
+
+    /// Inverse of `digest_to_bytes`; `None` if the bytes are no digest encoding
+    fn digest_from_bytes(bytes: &[u8]) -> Option<Self::Digest>;
+}
+
+/// A digest as lowercase hex of its byte encoding, for logs
+pub fn digest_hex<H: Hasher>(digest: &H::Digest) -> String {
+    to_hex(&H::digest_to_bytes(digest))
+}
+
+/// The original digit-based i128 mixer. Fast and readable, but NOT secure.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ToyHasher;
+
+impl Hasher for ToyHasher {
+    type Digest = i128;
+
+    /// Collisions are easy to find: the mixer only looks at decimal digits
+    const COLLISION_RESISTANCE: u32 = 0;
+    const DIGEST_SIZE: usize = 16;
+
+    fn hash_bytes(data: &[u8]) -> i128 {
+        let mut state = hash(data.len() as i128);
+        // Pack up to 15 bytes per limb so a limb always fits in an i128
+        for chunk in data.chunks(15) {
+            let limb = chunk.iter().fold(0i128, |acc, &b| (acc << 8) | b as i128);
+            state = hash(state.wrapping_add(limb.rotate_left(1)));
+        }
+        state
+    }
+
+    /// Column hashes folded left to right; a single value hashes to `hash(value)`
+    fn hash_leaf<F: Field>(values: &[F]) -> i128 {
+        let Some((first, rest)) = values.split_first() else {
+            return 0;
+        };
+        rest.iter().fold(toy_element_hash(first), |acc, v| {
+            Self::hash_node(&acc, &toy_element_hash(v))
+        })
+    }
+
+    fn hash_node(left: &i128, right: &i128) -> i128 {
+        let hl = hash(*left);
+        let hr = hash(*right);
+        hash(hl.wrapping_mul(1_000_003).wrapping_add(hr))
+    }
+
+    fn digest_to_bytes(digest: &i128) -> Vec<u8> {
+        digest.to_le_bytes().to_vec()
+    }
+
+    fn digest_from_bytes(bytes: &[u8]) -> Option<i128> {
+        Some(i128::from_le_bytes(bytes.try_into().ok()?))
+    }
+}
+
+pub fn hash(input: i128) -> i128 {
+    let mut hash: i128 = 3;
+    let mut num = input.wrapping_mul(100003); // biggish prime to make all inputs of at least certain size
+    while num != 0 {
+        let digit = num % 10;
+        hash = hash.wrapping_mul(113); // Shift by a prime number so digit positions make a difference
+        hash = hash.wrapping_add(digit);
+        num /= 10;
+    }
+    hash
+}
+
+/// Toy hash of one element: `hash` of its canonical value, with the 8-byte limbs
+/// of wider encodings folded in one by one
+fn toy_element_hash<F: Field>(value: &F) -> i128 {
+    let bytes = value.to_canonical_bytes();
+    let mut limbs = bytes.chunks(8).map(|chunk| {
+        let mut limb = [0u8; 8];
+        limb[..chunk.len()].copy_from_slice(chunk);
+        hash(u64::from_le_bytes(limb) as i128)
+    });
+    let first = limbs.next().unwrap_or_else(|| hash(0));
+    limbs.fold(first, |acc, limb| ToyHasher::hash_node(&acc, &limb))
+}
+
+/// Byte encoding of a leaf for the byte-oriented hashers: a 0x00 prefix, then each
+/// element in its canonical encoding
+fn leaf_bytes<F: Field>(values: &[F]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(1 + F::ENCODED_SIZE * values.len());
+    bytes.push(0x00);
+    for v in values {
+        bytes.extend_from_slice(&v.to_canonical_bytes());
+    }
+    bytes
+}
+
+/// Byte encoding of an inner node: a 0x01 prefix, then both children
+fn node_bytes(left: &[u8], right: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(1 + left.len() + right.len());
+    bytes.push(0x01);
+    bytes.extend_from_slice(left);
+    bytes.extend_from_slice(right);
+    bytes
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::finite_field::FiniteFieldElement;
+
+    fn digest_round_trip<H: Hasher>() {
+        let digest = H::hash_bytes(b"digest");
+        let bytes = H::digest_to_bytes(&digest);
+        assert_eq!(bytes.len(), H::DIGEST_SIZE);
+        assert_eq!(H::digest_from_bytes(&bytes), Some(digest));
+        assert_eq!(H::digest_from_bytes(&bytes[1..]), None);
+        assert_eq!(digest_hex::<H>(&digest).len(), 2 * H::DIGEST_SIZE);
+    }
+
+    #[test]
+    fn digests_round_trip_through_bytes() {
+        digest_round_trip::<ToyHasher>();
+        digest_round_trip::<Sha256>();
+        digest_round_trip::<Blake2s>();
+        digest_round_trip::<Poseidon>();
+    }
+
+    #[test]
+    fn small_are_not_identical() {
+        let mut found_hashes = HashMap::<i128, bool>::new();
+        for i in 0..10000 {
+            let hash = hash(i);
+            assert!(!found_hashes.contains_key(&hash));
+            found_hashes.insert(hash, true);
+            // println!("hash {} {}", i, hash);
+        }
+    }
+
+    #[test]
+    fn test_simple_hash() {
+        assert!(hash(2) > 0);
+        assert!(hash(3) > 0);
+        assert_eq!(hash(2), hash(2));
+        assert_ne!(hash(2), hash(3));
+
+        assert_ne!(hash(234), hash(324));
+        assert_ne!(hash(234), hash(432));
+
+        assert_ne!(hash(234), hash(23));
+        assert_ne!(hash(234), hash(34));
+
+        let max_value: i128 = i128::MAX;
+
+        // Test wrapping
+        assert_ne!(hash(max_value), hash(max_value - 1));
+        assert_ne!(hash(max_value), hash(max_value / 10));
+
+        let max_value_str = max_value.to_string();
+        let without_left_most_digit = &max_value_str[1..]; // Slice to remove the first character
+        let without_left_most_digit = without_left_most_digit.parse::<i128>().unwrap();
+
+        assert_ne!(hash(max_value), hash(without_left_most_digit));
+    }
+
+    #[test]
+    fn toy_hasher_is_ordered_and_length_aware() {
+        assert_ne!(ToyHasher::hash_node(&1, &2), ToyHasher::hash_node(&2, &1));
+        assert_ne!(ToyHasher::hash_bytes(b""), ToyHasher::hash_bytes(b"\0"));
+        assert_ne!(ToyHasher::hash_bytes(b"ab"), ToyHasher::hash_bytes(b"ba"));
+
+        let one = FiniteFieldElement::new(7);
+        assert_eq!(ToyHasher::hash_leaf(&[one]), hash(7));
+        assert_ne!(
+            ToyHasher::hash_leaf(&[one, FiniteFieldElement::new(8)]),
+            ToyHasher::hash_leaf(&[FiniteFieldElement::new(8), one])
+        );
+
+        // Leaves hash canonical values: 1 and 6 are the same element mod 5
+        type F5 = FiniteFieldElement<5>;
+        assert_eq!(
+            ToyHasher::hash_leaf(&[F5::from_i128(1)]),
+            ToyHasher::hash_leaf(&[F5::from_i128(6)])
+        );
+        assert_ne!(
+            ToyHasher::hash_leaf(&[F5::from_i128(0)]),
+            ToyHasher::hash_leaf(&[F5::from_i128(4)])
+        );
+    }
+
+    fn merkle_and_transcript_work_with<H: Hasher>() {
+        use crate::constants::DEFAULT_FIELD_SIZE;
+        use crate::fiat_shamir::Transcript;
+        use crate::merkle_tree::MerkleTree;
+
+        type F = FiniteFieldElement;
+        let elements: Vec<F> = (1..=5).map(F::new).collect();
+        let mut tree = MerkleTree::<H>::new();
+        tree.build(&elements);
+        let root = tree.root().unwrap();
+
+        let proof = tree.get_merkle_proof(2).unwrap();
+        let leaf = H::hash_leaf(&elements[2..3]);
+        assert!(MerkleTree::<H>::verify(root, 2, leaf, &proof));
+        assert!(!MerkleTree::<H>::verify(root, 3, leaf, &proof));
+
+        // A leaf and a node never collide thanks to the prefixes
+        assert_ne!(H::hash_leaf(&elements[..2]), H::hash_node(&leaf, &leaf));
+        // Canonical encoding: -1 and p - 1 are the same element
+        assert_eq!(
+            H::hash_leaf(&[F::new(-1)]),
+            H::hash_leaf(&[F::new(DEFAULT_FIELD_SIZE - 1)])
+        );
+        // Leaves of a small field hash too
+        let small = [FiniteFieldElement::<17>::from_i128(3)];
+        assert_eq!(H::hash_leaf(&small), H::hash_leaf(&small));
+        assert_ne!(H::hash_leaf(&small), H::hash_leaf(&[F::new(4)]));
+
+        let mut t1 = Transcript::<H>::new(b"test");
+        let mut t2 = Transcript::<H>::new(b"test");
+        t1.append_root(b"root", root);
+        t2.append_root(b"root", root);
+        assert_eq!(t1.challenge::<F>(b"c"), t2.challenge::<F>(b"c"));
+        t2.append_u64(b"n", 1);
+        assert_ne!(t1.challenge::<F>(b"c"), t2.challenge::<F>(b"c"));
+    }
+
+    #[test]
+    fn sha256_hasher_backs_merkle_and_transcript() {
+        merkle_and_transcript_work_with::<Sha256>();
+    }
+
+    #[test]
+    fn blake2s_hasher_backs_merkle_and_transcript() {
+        merkle_and_transcript_work_with::<Blake2s>();
+    }
+
+    #[test]
+    fn poseidon_hasher_backs_merkle_and_transcript() {
+        merkle_and_transcript_work_with::<Poseidon>();
+    }
+}
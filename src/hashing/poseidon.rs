@@ -0,0 +1,328 @@
+//! Poseidon permutation and sponge over the default STARK field (p = 3·2^30 + 1).
+//!
+//! Parameters: width 12 (rate 8, capacity 4), S-box x^5 (5 is coprime to p - 1),
+//! 8 full and 22 partial rounds. Round constants and the MDS matrix are generated
+//! here, in the open, so there is nothing up our sleeve:
+//! - round constants are SHA-256("Poseidon" || p || counter) reduced modulo p
+//! - the MDS matrix is the Cauchy matrix 1 / (x_i + y_j), x_i = i, y_j = WIDTH + j
+//!
+//! The round counts follow the usual margins for this width and field size but
+//! were not derived from a full security analysis; educational use only.
+
+use std::sync::OnceLock;
+
+use crate::constants::DEFAULT_FIELD_SIZE;
+use crate::field::Field;
+use crate::finite_field::FiniteFieldElement;
+
+use super::sha256::sha256;
+use super::Hasher;
+
+pub const WIDTH: usize = 12;
+pub const RATE: usize = 8;
+pub const CAPACITY: usize = WIDTH - RATE;
+/// Digest size in field elements (~124 bits)
+pub const DIGEST_LEN: usize = 4;
+
+const ALPHA: u64 = 5;
+const FULL_ROUNDS: usize = 8;
+const PARTIAL_ROUNDS: usize = 22;
+
+/// Capacity tags keeping the different uses of the permutation apart
+const DOMAIN_LEAF: i128 = 1;
+const DOMAIN_NODE: i128 = 2;
+const DOMAIN_BYTES: i128 = 3;
+
+struct PoseidonParams {
+    round_constants: Vec<[FiniteFieldElement; WIDTH]>,
+    mds: [[FiniteFieldElement; WIDTH]; WIDTH],
+}
+
+/// Round constants and MDS matrix, generated on first use
+fn params() -> &'static PoseidonParams {
+    static PARAMS: OnceLock<PoseidonParams> = OnceLock::new();
+    PARAMS.get_or_init(|| {
+        let mut counter: u32 = 0;
+        let mut next_constant = || {
+            let mut seed = b"Poseidon".to_vec();
+            seed.extend_from_slice(&DEFAULT_FIELD_SIZE.to_le_bytes());
+            seed.extend_from_slice(&counter.to_le_bytes());
+            counter += 1;
+            let digest = sha256(&seed);
+            let raw = u64::from_le_bytes(digest[..8].try_into().unwrap());
+            FiniteFieldElement::new(raw as i128)
+        };
+        let round_constants = (0..FULL_ROUNDS + PARTIAL_ROUNDS)
+            .map(|_| core::array::from_fn(|_| next_constant()))
+            .collect();
+
+        let mds = core::array::from_fn(|i| {
+            core::array::from_fn(|j| FiniteFieldElement::new((i + WIDTH + j) as i128).inverse())
+        });
+
+        PoseidonParams {
+            round_constants,
+            mds,
+        }
+    })
+}
+
+fn sbox(x: FiniteFieldElement) -> FiniteFieldElement {
+    x.pow(ALPHA)
+}
+
+/// The Poseidon permutation: R_F/2 full rounds, R_P partial rounds (S-box on the
+/// first element only), R_F/2 full rounds. Each round adds constants, applies the
+/// S-box layer and multiplies by the MDS matrix.
+pub fn permute(state: &mut [FiniteFieldElement; WIDTH]) {
+    let params = params();
+    let half_full = FULL_ROUNDS / 2;
+    for (round, constants) in params.round_constants.iter().enumerate() {
+        for (s, c) in state.iter_mut().zip(constants) {
+            *s = s.add(*c);
+        }
+        let full = round < half_full || round >= half_full + PARTIAL_ROUNDS;
+        if full {
+            for s in state.iter_mut() {
+                *s = sbox(*s);
+            }
+        } else {
+            state[0] = sbox(state[0]);
+        }
+        let mixed: [FiniteFieldElement; WIDTH] = core::array::from_fn(|i| {
+            params.mds[i]
+                .iter()
+                .zip(state.iter())
+                .fold(FiniteFieldElement::ZERO, |acc, (m, s)| {
+                    acc.add(m.multiply(*s))
+                })
+        });
+        *state = mixed;
+    }
+}
+
+/// Sponge over the Poseidon permutation: elements are added into the rate part,
+/// the capacity holds a domain tag. Squeezing pads the input with 1 0* first.
+#[derive(Debug, Clone)]
+pub struct PoseidonSponge {
+    state: [FiniteFieldElement; WIDTH],
+    /// Next rate position to absorb into or squeeze from
+    position: usize,
+    squeezing: bool,
+}
+
+impl PoseidonSponge {
+    pub fn new(domain: i128) -> Self {
+        let mut state = [FiniteFieldElement::ZERO; WIDTH];
+        state[RATE] = FiniteFieldElement::new(domain);
+        PoseidonSponge {
+            state,
+            position: 0,
+            squeezing: false,
+        }
+    }
+
+    /// Absorb elements of the Poseidon field
+    pub fn absorb(&mut self, values: &[FiniteFieldElement]) {
+        assert!(!self.squeezing, "cannot absorb after squeezing");
+        for v in values {
+            if self.position == RATE {
+                permute(&mut self.state);
+                self.position = 0;
+            }
+            self.state[self.position] = self.state[self.position].add(*v);
+            self.position += 1;
+        }
+    }
+
+    pub fn squeeze(&mut self, n: usize) -> Vec<FiniteFieldElement> {
+        if !self.squeezing {
+            if self.position == RATE {
+                permute(&mut self.state);
+                self.position = 0;
+            }
+            self.state[self.position] = self.state[self.position].add(FiniteFieldElement::ONE);
+            permute(&mut self.state);
+            self.position = 0;
+            self.squeezing = true;
+        }
+        let mut out = Vec::with_capacity(n);
+        while out.len() < n {
+            if self.position == RATE {
+                permute(&mut self.state);
+                self.position = 0;
+            }
+            out.push(self.state[self.position]);
+            self.position += 1;
+        }
+        out
+    }
+
+    fn squeeze_digest(&mut self) -> [FiniteFieldElement; DIGEST_LEN] {
+        self.squeeze(DIGEST_LEN).try_into().unwrap()
+    }
+}
+
+/// Poseidon as a Merkle / transcript hasher. Digests are four field elements.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Poseidon;
+
+impl Hasher for Poseidon {
+    type Digest = [FiniteFieldElement; DIGEST_LEN];
+
+    /// Half of the ~124-bit digest
+    const COLLISION_RESISTANCE: u32 = 62;
+    const DIGEST_SIZE: usize = 4 * DIGEST_LEN;
+
+    /// Bytes are packed three per element (below p), after their length
+    fn hash_bytes(data: &[u8]) -> Self::Digest {
+        let mut sponge = PoseidonSponge::new(DOMAIN_BYTES);
+        sponge.absorb(&[FiniteFieldElement::new(data.len() as i128)]);
+        sponge.absorb(&pack_bytes(data));
+        sponge.squeeze_digest()
+    }
+
+    /// Elements of a field no larger than Poseidon's are absorbed as they are;
+    /// wider ones are packed from their canonical encoding
+    fn hash_leaf<F: Field>(values: &[F]) -> Self::Digest {
+        let mut sponge = PoseidonSponge::new(DOMAIN_LEAF);
+        for v in values {
+            let bytes = v.to_canonical_bytes();
+            if F::CHARACTERISTIC <= DEFAULT_FIELD_SIZE as u64 && bytes.len() <= 8 {
+                let mut value = [0u8; 8];
+                value[..bytes.len()].copy_from_slice(&bytes);
+                sponge.absorb(&[FiniteFieldElement::new(u64::from_le_bytes(value) as i128)]);
+            } else {
+                sponge.absorb(&pack_bytes(&bytes));
+            }
+        }
+        sponge.squeeze_digest()
+    }
+
+    /// Two digests fill the rate exactly: one permutation per node
+    fn hash_node(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+        let mut state = [FiniteFieldElement::ZERO; WIDTH];
+        state[..DIGEST_LEN].copy_from_slice(left);
+        state[DIGEST_LEN..RATE].copy_from_slice(right);
+        state[RATE] = FiniteFieldElement::new(DOMAIN_NODE);
+        permute(&mut state);
+        state[..DIGEST_LEN].try_into().unwrap()
+    }
+
+    /// Each element as its canonical value in 4 little-endian bytes (p < 2^32)
+    fn digest_to_bytes(digest: &Self::Digest) -> Vec<u8> {
+        digest
+            .iter()
+            .flat_map(|e| (e.value as u32).to_le_bytes())
+            .collect()
+    }
+
+    /// Every element must be canonical
+    fn digest_from_bytes(bytes: &[u8]) -> Option<Self::Digest> {
+        if bytes.len() != Self::DIGEST_SIZE {
+            return None;
+        }
+        let mut digest = [FiniteFieldElement::ZERO; DIGEST_LEN];
+        for (e, chunk) in digest.iter_mut().zip(bytes.chunks_exact(4)) {
+            let value = u32::from_le_bytes(chunk.try_into().unwrap()) as i128;
+            if value >= DEFAULT_FIELD_SIZE {
+                return None;
+            }
+            *e = FiniteFieldElement::new(value);
+        }
+        Some(digest)
+    }
+}
+
+/// Bytes packed three per element, little-endian, so every element is below p
+fn pack_bytes(data: &[u8]) -> Vec<FiniteFieldElement> {
+    data.chunks(3)
+        .map(|chunk| {
+            let limb = chunk
+                .iter()
+                .rev()
+                .fold(0i128, |acc, &b| (acc << 8) | b as i128);
+            FiniteFieldElement::new(limb)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gcd(a: i128, b: i128) -> i128 {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
+    }
+
+    #[test]
+    fn sbox_is_a_permutation() {
+        assert_eq!(gcd(ALPHA as i128, DEFAULT_FIELD_SIZE - 1), 1);
+    }
+
+    #[test]
+    fn mds_matrix_is_invertible() {
+        // Gaussian elimination: every pivot must be found
+        let mut m = params().mds;
+        for col in 0..WIDTH {
+            let pivot = (col..WIDTH).find(|&r| !m[r][col].is_zero());
+            let pivot = pivot.expect("singular MDS matrix");
+            m.swap(col, pivot);
+            let inv = m[col][col].inverse();
+            let pivot_row = m[col];
+            for row in m.iter_mut().skip(col + 1) {
+                let factor = row[col].multiply(inv);
+                for (x, p) in row.iter_mut().zip(pivot_row.iter()).skip(col) {
+                    *x = x.subtract(factor.multiply(*p));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn permutation_is_deterministic_and_mixes() {
+        let mut a = [FiniteFieldElement::ZERO; WIDTH];
+        let mut b = a;
+        permute(&mut a);
+        permute(&mut b);
+        assert_eq!(a, b);
+        assert!(a.iter().any(|e| !e.is_zero()));
+
+        // Changing one input element changes every output element
+        let mut c = [FiniteFieldElement::ZERO; WIDTH];
+        c[WIDTH - 1] = FiniteFieldElement::ONE;
+        permute(&mut c);
+        for (x, y) in a.iter().zip(c.iter()) {
+            assert_ne!(x, y);
+        }
+    }
+
+    #[test]
+    fn sponge_pads_and_streams() {
+        let values: Vec<FiniteFieldElement> = (0..20).map(FiniteFieldElement::new).collect();
+
+        // Absorbing in pieces is the same as absorbing at once
+        let mut whole = PoseidonSponge::new(DOMAIN_LEAF);
+        whole.absorb(&values);
+        let mut pieces = PoseidonSponge::new(DOMAIN_LEAF);
+        pieces.absorb(&values[..3]);
+        pieces.absorb(&values[3..]);
+        assert_eq!(whole.squeeze(10), pieces.squeeze(10));
+
+        // Padding separates inputs that differ only by trailing zeros
+        assert_ne!(
+            Poseidon::hash_leaf(&values[..8]),
+            Poseidon::hash_leaf(&[&values[..8], &[FiniteFieldElement::ZERO]].concat())
+        );
+        // Domain tags separate bytes from elements
+        assert_ne!(
+            Poseidon::hash_bytes(b""),
+            Poseidon::hash_leaf::<FiniteFieldElement>(&[])
+        );
+        assert_ne!(Poseidon::hash_bytes(b"\0"), Poseidon::hash_bytes(b""));
+    }
+}
@@ -1,4 +1,5 @@
-use crate::{finite_field::FiniteFieldElement, hashing};
+use crate::field::Field;
+use crate::hashing::{Hasher, ToyHasher};
 
 // Helper function to find the next power of 2
 fn next_power_of_two(n: usize) -> usize {
@@ -11,75 +12,88 @@ fn next_power_of_two(n: usize) -> usize {
     n.next_power_of_two()
 }
 
-// Proper two-input hash function for Merkle tree nodes
+// Two-input node hash of the toy hasher. Ordered: swapping the children changes
+// the parent, so a path is bound to its leaf position
 pub fn hash_two_inputs(a: i128, b: i128) -> i128 {
-    let ha = hashing::hash(a);
-    let hb = hashing::hash(b);
-    // Commutative hashing
-    let (lo, hi) = if ha <= hb { (ha, hb) } else { (hb, ha) };
-    hashing::hash(lo.wrapping_add(hi))
+    ToyHasher::hash_node(&a, &b)
 }
 
-#[derive(Debug)]
-pub struct MerkleTree {
+/// Toy leaf hash of a trace row: all column values folded together with `hash_two_inputs`
+pub fn hash_row<F: Field>(values: &[F]) -> i128 {
+    ToyHasher::hash_leaf(values)
+}
+
+/// Authentication path of one leaf: its index and the sibling hashes from the
+/// leaf level up to (not including) the root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleProof<D = i128> {
+    pub index: usize,
+    pub siblings: Vec<D>,
+}
+
+/// Authentication of several leaves at once: the sorted, distinct queried indices
+/// and only the sibling hashes that cannot be computed from the queried leaves,
+/// level by level from the leaves up, left to right within a level
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleMultiProof<D = i128> {
+    pub indices: Vec<usize>,
+    pub siblings: Vec<D>,
+}
+
+/// Binary Merkle tree over `H` digests (the toy hash by default)
+#[derive(Debug, Default)]
+pub struct MerkleTree<H: Hasher = ToyHasher> {
     /// Root hash value
-    root: Option<i128>,
+    root: Option<H::Digest>,
     /// Nodes of the Merkle tree. Index 0 is leaves
-    nodes: Vec<Vec<i128>>,
-    /// Padded leaves as field elements (matches leaf hash layer length)
-    padded_leaves: Vec<FiniteFieldElement>,
+    nodes: Vec<Vec<H::Digest>>,
 }
 
-impl MerkleTree {
+impl<H: Hasher> MerkleTree<H> {
     pub fn new() -> Self {
         MerkleTree {
             root: None,
             nodes: Vec::new(),
-            padded_leaves: Vec::new(),
         }
     }
 
-    pub fn build(&mut self, elements: &[FiniteFieldElement]) {
+    pub fn build<F: Field>(&mut self, elements: &[F]) {
         if elements.is_empty() {
             self.root = None;
             self.nodes = vec![vec![]];
-            self.padded_leaves.clear();
             return;
         }
 
         // Hash the elements and call build_from_hashes
-        let hashes: Vec<i128> = elements.iter().map(|e| e.hash()).collect();
+        let hashes: Vec<H::Digest> = elements
+            .iter()
+            .map(|e| H::hash_leaf(core::slice::from_ref(e)))
+            .collect();
         self.build_from_hashes(&hashes);
     }
 
     /// Build a tree directly from precomputed leaf hashes (i128).
     /// Pads with zero hashes to the next power of two.
-    pub fn build_from_hashes(&mut self, leaf_hashes: &[i128]) {
+    pub fn build_from_hashes(&mut self, leaf_hashes: &[H::Digest]) {
         if leaf_hashes.is_empty() {
             self.root = None;
             self.nodes = vec![vec![]];
-            self.padded_leaves.clear();
             return;
         }
 
-        let mut hashes: Vec<i128> = leaf_hashes.to_vec();
+        let mut hashes: Vec<H::Digest> = leaf_hashes.to_vec();
         let target_size = next_power_of_two(hashes.len());
         while hashes.len() < target_size {
-            hashes.push(0);
+            hashes.push(H::Digest::default());
         }
 
-        // For compatibility, expose padded leaves as zeros of same length
-        self.padded_leaves = (0..target_size)
-            .map(|_| FiniteFieldElement::new(0))
-            .collect();
-
         let mut nodes = Vec::new();
         nodes.push(hashes.clone());
 
         while hashes.len() > 1 {
             let mut new_hashes = Vec::new();
             for chunk in hashes.chunks(2) {
-                let hash = hash_two_inputs(chunk[0], chunk[1]);
+                let hash = H::hash_node(&chunk[0], &chunk[1]);
                 new_hashes.push(hash);
             }
             nodes.push(new_hashes.clone());
@@ -89,7 +103,7 @@ impl MerkleTree {
         self.nodes = nodes;
     }
 
-    pub fn root(&self) -> Option<i128> {
+    pub fn root(&self) -> Option<H::Digest> {
         self.root
     }
 
@@ -102,46 +116,164 @@ impl MerkleTree {
         }
     }
 
-    /// Access the padded leaves used when building the tree
-    pub fn padded_leaves(&self) -> &[FiniteFieldElement] {
-        &self.padded_leaves
+    pub fn get_merkle_proof(&self, index: usize) -> Option<MerkleProof<H::Digest>> {
+        if index >= self.leaf_count() {
+            return None;
+        }
+        let mut siblings = Vec::new();
+        let mut idx = index;
+        // Every level except the root contributes one sibling
+        for level in &self.nodes[..self.nodes.len() - 1] {
+            siblings.push(level[idx ^ 1]);
+            idx /= 2;
+        }
+        Some(MerkleProof { index, siblings })
     }
 
-    pub fn get_merkle_proof(&self, index: usize) -> Option<Vec<i128>> {
-        if index >= self.nodes[0].len() {
-            return None;
+    /// Check that `leaf_hash` sits at position `index` under `root`. The bits of
+    /// the index decide on which side each sibling is hashed in
+    pub fn verify(
+        root: H::Digest,
+        index: usize,
+        leaf_hash: H::Digest,
+        proof: &MerkleProof<H::Digest>,
+    ) -> bool {
+        if proof.index != index || proof.siblings.len() >= usize::BITS as usize {
+            return false;
         }
-        let mut proof = Vec::new();
+        if index >> proof.siblings.len() != 0 {
+            return false;
+        }
+        let mut current_hash = leaf_hash;
         let mut idx = index;
-        for level in self.nodes.iter() {
-            if level.len() == 1 {
-                proof.push(level[0]);
-                break; // Reached the root node, no need to continue
-            }
-            let sibling_idx = if idx % 2 == 0 { idx + 1 } else { idx - 1 };
-            proof.push(level[sibling_idx]);
+        for &sibling in &proof.siblings {
+            current_hash = if idx.is_multiple_of(2) {
+                H::hash_node(&current_hash, &sibling)
+            } else {
+                H::hash_node(&sibling, &current_hash)
+            };
             idx /= 2;
         }
-        Some(proof)
+        current_hash == root
+    }
+
+    /// One proof for many leaves. Nodes shared between the individual paths, and
+    /// siblings that are themselves queried, are included once or not at all
+    pub fn get_multi_proof(&self, indices: &[usize]) -> Option<MerkleMultiProof<H::Digest>> {
+        if indices.iter().any(|&i| i >= self.leaf_count()) {
+            return None;
+        }
+        let mut known = indices.to_vec();
+        known.sort_unstable();
+        known.dedup();
+        let queried = known.clone();
+
+        let mut siblings = Vec::new();
+        for level in &self.nodes[..self.nodes.len().saturating_sub(1)] {
+            let mut i = 0;
+            while i < known.len() {
+                let pos = known[i];
+                if pos.is_multiple_of(2) && known.get(i + 1) == Some(&(pos + 1)) {
+                    // Both children known, the parent needs nothing
+                    i += 2;
+                } else {
+                    siblings.push(level[pos ^ 1]);
+                    i += 1;
+                }
+            }
+            known = known.iter().map(|pos| pos / 2).collect();
+            known.dedup();
+        }
+        Some(MerkleMultiProof {
+            indices: queried,
+            siblings,
+        })
+    }
+
+    /// Check a multi-proof for a tree with `leaf_count` leaves (padded to a power of
+    /// two): `leaf_hashes[k]` must sit at `indices[k]` under `root`. Indices may
+    /// repeat as long as they come with the same hash
+    pub fn verify_multi(
+        root: H::Digest,
+        leaf_count: usize,
+        indices: &[usize],
+        leaf_hashes: &[H::Digest],
+        proof: &MerkleMultiProof<H::Digest>,
+    ) -> bool {
+        if leaf_count == 0 || indices.is_empty() || indices.len() != leaf_hashes.len() {
+            return false;
+        }
+        let padded = next_power_of_two(leaf_count);
+
+        let mut known: Vec<(usize, H::Digest)> = indices
+            .iter()
+            .copied()
+            .zip(leaf_hashes.iter().copied())
+            .collect();
+        known.sort_by_key(|&(i, _)| i);
+        known.dedup();
+        // A repeated index with two different hashes survives dedup
+        if known.windows(2).any(|w| w[0].0 == w[1].0) {
+            return false;
+        }
+        if known.last().unwrap().0 >= padded
+            || !known
+                .iter()
+                .map(|&(i, _)| i)
+                .eq(proof.indices.iter().copied())
+        {
+            return false;
+        }
+
+        let mut siblings = proof.siblings.iter();
+        for _ in 0..padded.trailing_zeros() {
+            let mut parents = Vec::with_capacity(known.len());
+            let mut i = 0;
+            while i < known.len() {
+                let (pos, hash) = known[i];
+                let parent = if pos.is_multiple_of(2) {
+                    match known.get(i + 1) {
+                        Some(&(next, right)) if next == pos + 1 => {
+                            i += 1;
+                            H::hash_node(&hash, &right)
+                        }
+                        _ => match siblings.next() {
+                            Some(&right) => H::hash_node(&hash, &right),
+                            None => return false,
+                        },
+                    }
+                } else {
+                    match siblings.next() {
+                        Some(&left) => H::hash_node(&left, &hash),
+                        None => return false,
+                    }
+                };
+                parents.push((pos / 2, parent));
+                i += 1;
+            }
+            known = parents;
+        }
+        siblings.next().is_none() && known == [(0, root)]
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::finite_field::FiniteFieldElement;
     use crate::hashing::hash;
 
     use super::*;
 
     #[test]
     fn empty_tree() {
-        let tree = MerkleTree::new();
+        let tree: MerkleTree = MerkleTree::new();
         assert_eq!(tree.root, None);
         assert_eq!(tree.nodes.len(), 0);
     }
 
     #[test]
     fn build_empty_tree() {
-        let mut tree = MerkleTree::new();
+        let mut tree: MerkleTree = MerkleTree::new();
 
         let elements: Vec<FiniteFieldElement> = Vec::new();
         tree.build(&elements);
@@ -153,12 +285,10 @@ mod tests {
 
     #[test]
     fn build_tree_one_element() {
-        let mut tree = MerkleTree::new();
+        let mut tree: MerkleTree = MerkleTree::new();
 
         let val: i128 = 3;
-        let mut elements: Vec<FiniteFieldElement> = Vec::new();
-
-        elements.push(FiniteFieldElement::new(val));
+        let elements: Vec<FiniteFieldElement> = vec![FiniteFieldElement::new(val)];
         tree.build(&elements);
 
         let expected_leaf = hash(val);
@@ -172,14 +302,12 @@ mod tests {
 
     #[test]
     fn build_tree_two_elements() {
-        let mut tree = MerkleTree::new();
+        let mut tree: MerkleTree = MerkleTree::new();
 
         let val1: i128 = 3;
         let val2: i128 = 4;
-        let mut elements: Vec<FiniteFieldElement> = Vec::new();
-
-        elements.push(FiniteFieldElement::new(val1));
-        elements.push(FiniteFieldElement::new(val2));
+        let elements: Vec<FiniteFieldElement> =
+            vec![FiniteFieldElement::new(val1), FiniteFieldElement::new(val2)];
         tree.build(&elements);
 
         let expected_leaf_1 = hash(val1);
@@ -197,16 +325,16 @@ mod tests {
 
     #[test]
     fn build_tree_three_elements() {
-        let mut tree = MerkleTree::new();
+        let mut tree: MerkleTree = MerkleTree::new();
 
         let val1: i128 = 3;
         let val2: i128 = 4;
         let val3: i128 = 5;
-        let mut elements: Vec<FiniteFieldElement> = Vec::new();
-
-        elements.push(FiniteFieldElement::new(val1));
-        elements.push(FiniteFieldElement::new(val2));
-        elements.push(FiniteFieldElement::new(val3));
+        let elements: Vec<FiniteFieldElement> = vec![
+            FiniteFieldElement::new(val1),
+            FiniteFieldElement::new(val2),
+            FiniteFieldElement::new(val3),
+        ];
         tree.build(&elements);
 
         let expected_leaf_1 = hash(val1);
@@ -238,16 +366,16 @@ mod tests {
 
     #[test]
     fn get_merkle_proof_with_three_elements() {
-        let mut tree = MerkleTree::new();
+        let mut tree: MerkleTree = MerkleTree::new();
 
         let val1: i128 = 3;
         let val2: i128 = 4;
         let val3: i128 = 5;
-        let mut elements: Vec<FiniteFieldElement> = Vec::new();
-
-        elements.push(FiniteFieldElement::new(val1));
-        elements.push(FiniteFieldElement::new(val2));
-        elements.push(FiniteFieldElement::new(val3));
+        let elements: Vec<FiniteFieldElement> = vec![
+            FiniteFieldElement::new(val1),
+            FiniteFieldElement::new(val2),
+            FiniteFieldElement::new(val3),
+        ];
         tree.build(&elements);
 
         let expected_leaf_1 = hash(val1);
@@ -259,45 +387,46 @@ mod tests {
         let expected_mid_node2 = hash_two_inputs(expected_leaf_3, expected_leaf_4);
 
         let expected_root = hash_two_inputs(expected_mid_node1, expected_mid_node2);
+        assert_eq!(tree.root(), Some(expected_root));
 
         // Test proofs for each leaf
         {
             let proof = tree.get_merkle_proof(0).unwrap();
-            let expected_proof = vec![expected_leaf_2, expected_mid_node2, expected_root];
+            let expected_proof = [expected_leaf_2, expected_mid_node2];
 
-            assert_eq!(proof.len(), expected_proof.len());
+            assert_eq!(proof.siblings.len(), expected_proof.len());
 
-            for (elem1, elem2) in proof.iter().zip(expected_proof.iter()) {
+            for (elem1, elem2) in proof.siblings.iter().zip(expected_proof.iter()) {
                 assert_eq!(elem1, elem2); // Ensure each pair of corresponding elements is equal
             }
         }
         {
             let proof = tree.get_merkle_proof(1).unwrap();
-            let expected_proof = vec![expected_leaf_1, expected_mid_node2, expected_root];
+            let expected_proof = [expected_leaf_1, expected_mid_node2];
 
-            assert_eq!(proof.len(), expected_proof.len());
+            assert_eq!(proof.siblings.len(), expected_proof.len());
 
-            for (elem1, elem2) in proof.iter().zip(expected_proof.iter()) {
+            for (elem1, elem2) in proof.siblings.iter().zip(expected_proof.iter()) {
                 assert_eq!(elem1, elem2); // Ensure each pair of corresponding elements is equal
             }
         }
         {
             let proof = tree.get_merkle_proof(2).unwrap();
-            let expected_proof = vec![expected_leaf_4, expected_mid_node1, expected_root];
+            let expected_proof = [expected_leaf_4, expected_mid_node1];
 
-            assert_eq!(proof.len(), expected_proof.len());
+            assert_eq!(proof.siblings.len(), expected_proof.len());
 
-            for (elem1, elem2) in proof.iter().zip(expected_proof.iter()) {
+            for (elem1, elem2) in proof.siblings.iter().zip(expected_proof.iter()) {
                 assert_eq!(elem1, elem2); // Ensure each pair of corresponding elements is equal
             }
         }
         {
             let proof = tree.get_merkle_proof(3).unwrap();
-            let expected_proof = vec![expected_leaf_3, expected_mid_node1, expected_root];
+            let expected_proof = [expected_leaf_3, expected_mid_node1];
 
-            assert_eq!(proof.len(), expected_proof.len());
+            assert_eq!(proof.siblings.len(), expected_proof.len());
 
-            for (elem1, elem2) in proof.iter().zip(expected_proof.iter()) {
+            for (elem1, elem2) in proof.siblings.iter().zip(expected_proof.iter()) {
                 assert_eq!(elem1, elem2); // Ensure each pair of corresponding elements is equal
             }
         }
@@ -305,19 +434,16 @@ mod tests {
 
     #[test]
     fn test_padding_to_power_of_two() {
-        // Test that tree should properly pad to next power of 2
-        // For 5 elements, should pad to 8 (2^3)
-        let mut tree = MerkleTree::new();
+        // The tree pads to the next power of 2: 5 elements -> 8 leaves
+        let mut tree: MerkleTree = MerkleTree::new();
 
-        let elements: Vec<FiniteFieldElement> =
-            (1..=5).map(|i| FiniteFieldElement::new(i)).collect();
+        let elements: Vec<FiniteFieldElement> = (1..=5).map(FiniteFieldElement::new).collect();
 
         tree.build(&elements);
 
-        // This test will fail until we implement proper power-of-2 padding
-        // Should have 4 nodes: leaves (8), level 1 (4), level 2 (2), root (1)
+        // 4 levels: leaves (8), level 1 (4), level 2 (2), root (1)
         assert_eq!(tree.nodes.len(), 4);
-        assert_eq!(tree.nodes[0].len(), 8); // Should be padded to 8 leaves
+        assert_eq!(tree.nodes[0].len(), 8); // Padded to 8 leaves
         assert_eq!(tree.nodes[1].len(), 4);
         assert_eq!(tree.nodes[2].len(), 2);
         assert_eq!(tree.nodes[3].len(), 1);
@@ -328,30 +454,19 @@ mod tests {
             assert_eq!(tree.nodes[0][i], expected_hash);
         }
 
-        // Last 3 leaves should be proper padding (zeros or some default value)
-        // This test will fail until we implement proper padding
+        // Last 3 leaves are zero padding, not duplicates of the last element
         for i in 5..8 {
-            // Currently duplicates last element, should be padding
-            assert_eq!(tree.nodes[0][i], 0); // Should be padding, not duplicate
+            assert_eq!(tree.nodes[0][i], 0);
         }
     }
 
     #[test]
-    fn test_commutative_hashing() {
-        // Test that hash(a, b) == hash(b, a) for commutative property
-        // The current implementation uses hash(a + b) which is commutative
-        // But we need a proper two-input hash function
-
-        // Test the new two-input hash function
-        let hash1 = hash_two_inputs(1, 2);
-        let hash2 = hash_two_inputs(2, 1);
-
-        // This should pass with our new commutative hash function
-        assert_eq!(hash1, hash2);
+    fn test_ordered_hashing() {
+        // Node hashing is ordered, so swapping two leaves changes the root
+        assert_ne!(hash_two_inputs(1, 2), hash_two_inputs(2, 1));
 
-        // For now, let's test that the current approach works for trees
-        let mut tree1 = MerkleTree::new();
-        let mut tree2 = MerkleTree::new();
+        let mut tree1: MerkleTree = MerkleTree::new();
+        let mut tree2: MerkleTree = MerkleTree::new();
 
         let elements1: Vec<FiniteFieldElement> =
             vec![FiniteFieldElement::new(1), FiniteFieldElement::new(2)];
@@ -362,17 +477,15 @@ mod tests {
         tree1.build(&elements1);
         tree2.build(&elements2);
 
-        // This should pass with current implementation
-        assert_eq!(tree1.root(), tree2.root());
+        assert_ne!(tree1.root(), tree2.root());
     }
 
     #[test]
     fn test_merkle_proof_verification() {
         // Test that we can verify a merkle proof
-        let mut tree = MerkleTree::new();
+        let mut tree: MerkleTree = MerkleTree::new();
 
-        let elements: Vec<FiniteFieldElement> =
-            (1..=4).map(|i| FiniteFieldElement::new(i)).collect();
+        let elements: Vec<FiniteFieldElement> = (1..=4).map(FiniteFieldElement::new).collect();
 
         tree.build(&elements);
 
@@ -380,23 +493,86 @@ mod tests {
         let proof = tree.get_merkle_proof(0).unwrap();
         let leaf_hash = hash(1);
 
-        // Verify the proof by reconstructing the root by folding siblings
-        // The proof includes the root as the last element; exclude it while folding
-        let mut current_hash = leaf_hash;
-        for sibling in proof.iter().take(proof.len().saturating_sub(1)) {
-            current_hash = hash_two_inputs(current_hash, *sibling);
+        assert_eq!(proof.index, 0);
+        assert!(MerkleTree::<ToyHasher>::verify(
+            tree.root().unwrap(),
+            0,
+            leaf_hash,
+            &proof
+        ));
+
+        // Every leaf verifies at its own index
+        for i in 0..4 {
+            let proof = tree.get_merkle_proof(i).unwrap();
+            assert!(MerkleTree::<ToyHasher>::verify(
+                tree.root().unwrap(),
+                i,
+                hash(i as i128 + 1),
+                &proof
+            ));
         }
+        assert!(tree.get_merkle_proof(4).is_none());
+    }
 
-        assert_eq!(current_hash, tree.root().unwrap());
+    #[test]
+    fn merkle_proof_is_bound_to_its_index() {
+        let mut tree: MerkleTree = MerkleTree::new();
+        let elements: Vec<FiniteFieldElement> = (1..=8).map(FiniteFieldElement::new).collect();
+        tree.build(&elements);
+        let root = tree.root().unwrap();
+
+        let proof = tree.get_merkle_proof(3).unwrap();
+        assert!(MerkleTree::<ToyHasher>::verify(root, 3, hash(4), &proof));
+
+        // The same path cannot be passed off as another row
+        assert!(!MerkleTree::<ToyHasher>::verify(root, 2, hash(4), &proof));
+        let relabeled = MerkleProof {
+            index: 2,
+            siblings: proof.siblings.clone(),
+        };
+        assert!(!MerkleTree::<ToyHasher>::verify(
+            root,
+            2,
+            hash(4),
+            &relabeled
+        ));
+        assert!(!MerkleTree::<ToyHasher>::verify(
+            root,
+            2,
+            hash(3),
+            &relabeled
+        ));
+
+        // Indices beyond the tree are rejected even with matching hashes
+        let out_of_range = MerkleProof {
+            index: 3 + 8,
+            siblings: proof.siblings.clone(),
+        };
+        assert!(!MerkleTree::<ToyHasher>::verify(
+            root,
+            3 + 8,
+            hash(4),
+            &out_of_range
+        ));
+
+        // Wrong leaf or tampered sibling
+        assert!(!MerkleTree::<ToyHasher>::verify(root, 3, hash(5), &proof));
+        let mut tampered = proof.clone();
+        tampered.siblings[1] += 1;
+        assert!(!MerkleTree::<ToyHasher>::verify(
+            root,
+            3,
+            hash(4),
+            &tampered
+        ));
     }
 
     #[test]
     fn test_large_tree_current_padding() {
         // Test current padding behavior for larger tree (13 elements -> 16)
-        let mut tree = MerkleTree::new();
+        let mut tree: MerkleTree = MerkleTree::new();
 
-        let elements: Vec<FiniteFieldElement> =
-            (1..=13).map(|i| FiniteFieldElement::new(i)).collect();
+        let elements: Vec<FiniteFieldElement> = (1..=13).map(FiniteFieldElement::new).collect();
 
         tree.build(&elements);
 
@@ -423,17 +599,15 @@ mod tests {
     #[test]
     fn test_large_tree_proper_padding() {
         // Test proper padding for larger tree (13 elements -> 16)
-        let mut tree = MerkleTree::new();
+        let mut tree: MerkleTree = MerkleTree::new();
 
-        let elements: Vec<FiniteFieldElement> =
-            (1..=13).map(|i| FiniteFieldElement::new(i)).collect();
+        let elements: Vec<FiniteFieldElement> = (1..=13).map(FiniteFieldElement::new).collect();
 
         tree.build(&elements);
 
-        // This test will fail until we implement proper power-of-2 padding
-        // Should have 5 nodes: 16, 8, 4, 2, 1
+        // 5 levels: 16, 8, 4, 2, 1
         assert_eq!(tree.nodes.len(), 5);
-        assert_eq!(tree.nodes[0].len(), 16); // Should be padded to 16
+        assert_eq!(tree.nodes[0].len(), 16); // Padded to 16 leaves
         assert_eq!(tree.nodes[1].len(), 8);
         assert_eq!(tree.nodes[2].len(), 4);
         assert_eq!(tree.nodes[3].len(), 2);
@@ -445,11 +619,188 @@ mod tests {
             assert_eq!(tree.nodes[0][i], expected_hash);
         }
 
-        // Last 3 leaves should be proper padding (not duplicates)
-        // This test will fail until we implement proper padding
+        // Last 3 leaves are zero padding, not duplicates of the last element
         for i in 13..16 {
-            // Currently duplicates last element, should be padding
-            assert_eq!(tree.nodes[0][i], 0); // Should be padding, not duplicate
+            assert_eq!(tree.nodes[0][i], 0);
         }
     }
+
+    #[test]
+    fn multi_proof_verifies_and_shares_nodes() {
+        let mut tree: MerkleTree = MerkleTree::new();
+        let elements: Vec<FiniteFieldElement> = (1..=1024).map(FiniteFieldElement::new).collect();
+        tree.build(&elements);
+        let root = tree.root().unwrap();
+
+        // 32 spread-out indices, some of them neighbours
+        let indices: Vec<usize> = (0..32).map(|k| (k * 37 + k * k * 11) % 1024).collect();
+        let leaf_hashes: Vec<i128> = indices.iter().map(|&i| hash(i as i128 + 1)).collect();
+        let proof = tree.get_multi_proof(&indices).unwrap();
+        assert!(MerkleTree::<ToyHasher>::verify_multi(
+            root,
+            1024,
+            &indices,
+            &leaf_hashes,
+            &proof
+        ));
+
+        // Size comparison against one full path per index
+        let separate: usize = indices
+            .iter()
+            .map(|&i| tree.get_merkle_proof(i).unwrap().siblings.len())
+            .sum();
+        assert_eq!(separate, 32 * 10);
+        assert!(proof.siblings.len() < separate * 3 / 4);
+
+        // Two neighbouring leaves need no sibling at the leaf level
+        let pair = tree.get_multi_proof(&[6, 7]).unwrap();
+        assert_eq!(pair.siblings.len(), 9);
+        assert_eq!(pair.siblings[0], tree.nodes[1][2]);
+
+        // All leaves need no siblings at all
+        let all: Vec<usize> = (0..1024).collect();
+        assert!(tree.get_multi_proof(&all).unwrap().siblings.is_empty());
+    }
+
+    #[test]
+    fn multi_proof_rejects_tampering() {
+        let mut tree: MerkleTree = MerkleTree::new();
+        let elements: Vec<FiniteFieldElement> = (1..=16).map(FiniteFieldElement::new).collect();
+        tree.build(&elements);
+        let root = tree.root().unwrap();
+
+        let indices = [3, 9, 3, 10];
+        let leaf_hashes: Vec<i128> = indices.iter().map(|&i| hash(i as i128 + 1)).collect();
+        let proof = tree.get_multi_proof(&indices).unwrap();
+        assert_eq!(proof.indices, vec![3, 9, 10]);
+        assert!(MerkleTree::<ToyHasher>::verify_multi(
+            root,
+            16,
+            &indices,
+            &leaf_hashes,
+            &proof
+        ));
+
+        // Wrong leaf value
+        let mut bad_hashes = leaf_hashes.clone();
+        bad_hashes[1] = hash(1);
+        assert!(!MerkleTree::<ToyHasher>::verify_multi(
+            root,
+            16,
+            &indices,
+            &bad_hashes,
+            &proof
+        ));
+
+        // Same index claimed with two different values
+        let mut conflicting = leaf_hashes.clone();
+        conflicting[2] = hash(5);
+        assert!(!MerkleTree::<ToyHasher>::verify_multi(
+            root,
+            16,
+            &indices,
+            &conflicting,
+            &proof
+        ));
+
+        // Leaves moved to other positions
+        assert!(!MerkleTree::<ToyHasher>::verify_multi(
+            root,
+            16,
+            &[2, 9, 2, 10],
+            &leaf_hashes,
+            &proof
+        ));
+
+        // Tampered, missing or extra siblings
+        let mut tampered = proof.clone();
+        tampered.siblings[0] += 1;
+        assert!(!MerkleTree::<ToyHasher>::verify_multi(
+            root,
+            16,
+            &indices,
+            &leaf_hashes,
+            &tampered
+        ));
+        let mut short = proof.clone();
+        short.siblings.pop();
+        assert!(!MerkleTree::<ToyHasher>::verify_multi(
+            root,
+            16,
+            &indices,
+            &leaf_hashes,
+            &short
+        ));
+        let mut long = proof.clone();
+        long.siblings.push(0);
+        assert!(!MerkleTree::<ToyHasher>::verify_multi(
+            root,
+            16,
+            &indices,
+            &leaf_hashes,
+            &long
+        ));
+
+        assert!(tree.get_multi_proof(&[16]).is_none());
+    }
+
+    /// Stand-in hasher with a byte-array digest, to exercise the generic tree
+    #[derive(Debug, Clone, Copy, Default)]
+    struct BytesHasher;
+
+    impl Hasher for BytesHasher {
+        type Digest = [u8; 16];
+
+        const COLLISION_RESISTANCE: u32 = 0;
+        const DIGEST_SIZE: usize = 16;
+
+        fn hash_bytes(data: &[u8]) -> [u8; 16] {
+            ToyHasher::hash_bytes(data).to_le_bytes()
+        }
+
+        fn hash_leaf<F: Field>(values: &[F]) -> [u8; 16] {
+            ToyHasher::hash_leaf(values).to_le_bytes()
+        }
+
+        fn hash_node(left: &[u8; 16], right: &[u8; 16]) -> [u8; 16] {
+            let (l, r) = (i128::from_le_bytes(*left), i128::from_le_bytes(*right));
+            ToyHasher::hash_node(&l, &r).to_le_bytes()
+        }
+
+        fn digest_to_bytes(digest: &[u8; 16]) -> Vec<u8> {
+            digest.to_vec()
+        }
+
+        fn digest_from_bytes(bytes: &[u8]) -> Option<[u8; 16]> {
+            bytes.try_into().ok()
+        }
+    }
+
+    #[test]
+    fn tree_is_generic_over_the_hasher() {
+        let elements: Vec<FiniteFieldElement> = (1..=6).map(FiniteFieldElement::new).collect();
+        let mut toy: MerkleTree = MerkleTree::new();
+        let mut bytes = MerkleTree::<BytesHasher>::new();
+        toy.build(&elements);
+        bytes.build(&elements);
+
+        let root = bytes.root().unwrap();
+        assert_eq!(root, toy.root().unwrap().to_le_bytes());
+        assert_eq!(bytes.leaf_count(), 8);
+
+        let leaf = BytesHasher::hash_leaf(&elements[4..5]);
+        let proof = bytes.get_merkle_proof(4).unwrap();
+        assert!(MerkleTree::<BytesHasher>::verify(root, 4, leaf, &proof));
+        assert!(!MerkleTree::<BytesHasher>::verify(root, 5, leaf, &proof));
+
+        let multi = bytes.get_multi_proof(&[1, 4]).unwrap();
+        let leaves = [BytesHasher::hash_leaf(&elements[1..2]), leaf];
+        assert!(MerkleTree::<BytesHasher>::verify_multi(
+            root,
+            8,
+            &[1, 4],
+            &leaves,
+            &multi
+        ));
+    }
 }
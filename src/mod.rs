@@ -1,11 +1,19 @@
+pub mod air;
 pub mod constants;
 pub mod evaluation_domain;
+pub mod extension_field;
 pub mod fiat_shamir;
+pub mod field;
 pub mod finite_field;
 pub mod fri;
+pub mod goldilocks;
 pub mod hashing;
 pub mod merkle_tree;
+pub mod montgomery;
 pub mod polynomial;
+pub mod proof_options;
 pub mod prover;
+pub mod security;
+pub mod serialization;
 pub mod trace;
 pub mod verifier;
@@ -0,0 +1,362 @@
+use core::fmt;
+
+use crate::constants::DEFAULT_FIELD_SIZE;
+use crate::field::Field;
+use crate::finite_field::{smallest_generator, FiniteFieldElement};
+
+/// Element of the prime field of order `P` < 2^32, kept in Montgomery form
+/// a · 2^32 mod P. A product then reduces with two multiplications and a shift
+/// in u64 instead of an i128 remainder. Same field as `FiniteFieldElement<P>`,
+/// with `From` conversions both ways.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MontgomeryElement<const P: u32 = { DEFAULT_FIELD_SIZE as u32 }> {
+    /// a · R mod P, with R = 2^32
+    montgomery: u32,
+}
+
+impl<const P: u32> MontgomeryElement<P> {
+    const MODULUS_IS_ODD: () = assert!(P > 2 && P % 2 == 1, "modulus must be an odd prime");
+
+    /// P^-1 mod 2^32 by Newton iteration; each step doubles the correct low bits
+    /// (P · P = 1 mod 8 gives the first three)
+    const P_INV: u32 = {
+        let mut inv = P;
+        let mut i = 0;
+        while i < 4 {
+            inv = inv.wrapping_mul(2u32.wrapping_sub(P.wrapping_mul(inv)));
+            i += 1;
+        }
+        inv
+    };
+
+    /// R^2 mod P, to move values into Montgomery form
+    const R2: u64 = {
+        let r = (1u64 << 32) % P as u64;
+        r * r % P as u64
+    };
+
+    /// Montgomery reduction: x · R^-1 mod P for x < P · R. With m = x · P^-1 mod R,
+    /// x - m · P is divisible by R and lies in (-P · R, P · R), so its high half
+    /// is the result up to one correction.
+    const fn reduce(x: u64) -> u32 {
+        let m = (x as u32).wrapping_mul(Self::P_INV);
+        let mp = m as u64 * P as u64;
+        // The low halves of x and m · P are equal, only the high halves differ
+        let (hi, borrow) = ((x >> 32) as u32).overflowing_sub((mp >> 32) as u32);
+        if borrow {
+            hi.wrapping_add(P)
+        } else {
+            hi
+        }
+    }
+
+    /// The element `value` mod P
+    pub const fn from_value(value: u64) -> Self {
+        let () = Self::MODULUS_IS_ODD;
+        MontgomeryElement {
+            montgomery: Self::reduce((value % P as u64) * Self::R2),
+        }
+    }
+
+    /// The canonical value in [0, P)
+    pub const fn value(&self) -> u32 {
+        Self::reduce(self.montgomery as u64)
+    }
+}
+
+impl MontgomeryElement {
+    /// Element of the default field
+    pub const fn new(value: u64) -> Self {
+        Self::from_value(value)
+    }
+}
+
+/// Zero
+impl<const P: u32> Default for MontgomeryElement<P> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<const P: u32> fmt::Debug for MontgomeryElement<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MontgomeryElement({})", self.value())
+    }
+}
+
+impl<const P: u32> fmt::Display for MontgomeryElement<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value())
+    }
+}
+
+/// Conversion from the same field in the plain representation; fails to compile
+/// if the moduli differ
+impl<const P: u32, const Q: i128> From<FiniteFieldElement<Q>> for MontgomeryElement<P> {
+    fn from(element: FiniteFieldElement<Q>) -> Self {
+        const { assert!(Q == P as i128, "conversion between different fields") };
+        Self::from_value(element.value as u64)
+    }
+}
+
+impl<const P: u32, const Q: i128> From<MontgomeryElement<P>> for FiniteFieldElement<Q> {
+    fn from(element: MontgomeryElement<P>) -> Self {
+        const { assert!(Q == P as i128, "conversion between different fields") };
+        FiniteFieldElement::from_value(element.value() as i128)
+    }
+}
+
+impl<const P: u32> Field for MontgomeryElement<P> {
+    const ZERO: Self = MontgomeryElement { montgomery: 0 };
+    const ONE: Self = Self::from_value(1);
+    const CHARACTERISTIC: u64 = P as u64;
+    const TWO_ADICITY: u32 = (P - 1).trailing_zeros();
+    const ENCODED_SIZE: usize = 4;
+
+    fn from_u64(value: u64) -> Self {
+        Self::from_value(value)
+    }
+
+    fn add(&self, other: Self) -> Self {
+        // Both are below P < 2^32, so the sum is below 2P
+        let (sum, carry) = self.montgomery.overflowing_add(other.montgomery);
+        let montgomery = if carry || sum >= P {
+            sum.wrapping_sub(P)
+        } else {
+            sum
+        };
+        MontgomeryElement { montgomery }
+    }
+
+    fn subtract(&self, other: Self) -> Self {
+        let (diff, borrow) = self.montgomery.overflowing_sub(other.montgomery);
+        let montgomery = if borrow { diff.wrapping_add(P) } else { diff };
+        MontgomeryElement { montgomery }
+    }
+
+    fn multiply(&self, other: Self) -> Self {
+        // (a · R)(b · R) / R = a · b · R
+        MontgomeryElement {
+            montgomery: Self::reduce(self.montgomery as u64 * other.montgomery as u64),
+        }
+    }
+
+    fn negate(&self) -> Self {
+        Self::ZERO.subtract(*self)
+    }
+
+    fn inverse(&self) -> Self {
+        // Fermat's little theorem: a^(p-2) mod p
+        self.pow(P as u64 - 2)
+    }
+
+    fn multiplicative_generator() -> Self {
+        smallest_generator()
+    }
+
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        self.value().to_le_bytes().to_vec()
+    }
+
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self> {
+        let value = u32::from_le_bytes(bytes.try_into().ok()?);
+        (value < P).then(|| Self::from_value(value as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polynomial::polynomial::Polynomial;
+    use crate::prover::extend_trace;
+    use crate::trace::Trace;
+    use std::time::{Duration, Instant};
+
+    type M = MontgomeryElement;
+    type F = FiniteFieldElement;
+    type M17 = MontgomeryElement<17>;
+
+    const P: u64 = DEFAULT_FIELD_SIZE as u64;
+
+    fn edge_values() -> Vec<u64> {
+        let mut values = vec![
+            0,
+            1,
+            2,
+            3,
+            1 << 30,
+            (1 << 31) - 1,
+            1 << 31,
+            P / 2,
+            P - 2,
+            P - 1,
+        ];
+        let mut x: u64 = 12345;
+        for _ in 0..32 {
+            x = x
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            values.push(x % P);
+        }
+        values
+    }
+
+    #[test]
+    fn constants() {
+        assert_eq!(M::P_INV.wrapping_mul(P as u32), 1);
+        assert_eq!(M17::P_INV.wrapping_mul(17), 1);
+        assert_eq!(M::R2, ((1u128 << 64) % P as u128) as u64);
+        assert_eq!(M::ONE.value(), 1);
+        assert_eq!(M::ZERO.value(), 0);
+        assert_eq!(M::TWO_ADICITY, 30);
+        assert_eq!(M::CHARACTERISTIC, P);
+    }
+
+    #[test]
+    fn values_round_trip() {
+        for v in edge_values() {
+            assert_eq!(M::new(v).value() as u64, v);
+        }
+        assert_eq!(M::new(P).value(), 0);
+        assert_eq!(M::new(u64::MAX).value() as u64, u64::MAX % P);
+        assert_eq!(M::from_i128(-1).value() as u64, P - 1);
+        assert_eq!(M17::from_u64(20).value(), 3);
+    }
+
+    #[test]
+    fn arithmetic_matches_finite_field_element() {
+        let values = edge_values();
+        for &a in &values {
+            for &b in &values {
+                let (x, y) = (M::new(a), M::new(b));
+                let (u, v) = (F::new(a as i128), F::new(b as i128));
+                assert_eq!(F::from(x.add(y)), u.add(v), "{} + {}", a, b);
+                assert_eq!(F::from(x.subtract(y)), u.subtract(v), "{} - {}", a, b);
+                assert_eq!(F::from(x.multiply(y)), u.multiply(v), "{} * {}", a, b);
+            }
+            let x = M::new(a);
+            assert_eq!(F::from(x.negate()), F::new(a as i128).negate());
+            if a != 0 {
+                assert_eq!(x.multiply(x.inverse()), M::ONE, "1 / {}", a);
+            }
+        }
+        assert_eq!(M::ZERO.inverse(), M::ZERO);
+    }
+
+    #[test]
+    fn conversions() {
+        for v in edge_values() {
+            let f = F::new(v as i128);
+            let m = M::from(f);
+            assert_eq!(m.value() as u64, v);
+            assert_eq!(F::from(m), f);
+        }
+        let small = FiniteFieldElement::<17>::from_value(12);
+        assert_eq!(M17::from(small).value(), 12);
+    }
+
+    #[test]
+    fn same_generator_and_roots_as_finite_field_element() {
+        assert_eq!(
+            F::from(M::multiplicative_generator()),
+            F::multiplicative_generator()
+        );
+        assert_eq!(
+            F::from(M::primitive_root_of_unity(1 << 10).unwrap()),
+            F::primitive_root_of_unity(1 << 10).unwrap()
+        );
+    }
+
+    #[test]
+    fn canonical_bytes() {
+        for v in edge_values() {
+            let bytes = M::new(v).to_canonical_bytes();
+            assert_eq!(bytes, (v as u32).to_le_bytes());
+            assert_eq!(M::from_canonical_bytes(&bytes), Some(M::new(v)));
+        }
+        assert_eq!(M::from_canonical_bytes(&(P as u32).to_le_bytes()), None);
+        assert_eq!(M::from_canonical_bytes(&[0; 8]), None);
+    }
+
+    fn sample_coefficients(n: usize, seed: u64) -> Vec<u64> {
+        let mut x = seed;
+        (0..n)
+            .map(|_| {
+                x = x
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                x % P
+            })
+            .collect()
+    }
+
+    fn polynomial<E: Field>(values: &[u64]) -> Polynomial<E> {
+        Polynomial::new_ff(values.iter().map(|&v| E::from_u64(v)).collect())
+    }
+
+    fn trace<E: Field>(rows: usize) -> Trace<E> {
+        Trace::from_computation(rows, 2, |step, _| {
+            let step = step as u64;
+            vec![E::from_u64(step), E::from_u64(step * step)]
+        })
+    }
+
+    #[test]
+    fn polynomial_multiply_and_lde_agree() {
+        let (a, b) = (sample_coefficients(100, 1), sample_coefficients(80, 2));
+        let product = polynomial::<M>(&a).multiply(&polynomial::<M>(&b));
+        let expected = polynomial::<F>(&a).multiply(&polynomial::<F>(&b));
+        let converted: Vec<F> = product.coefficients.into_iter().map(F::from).collect();
+        assert_eq!(converted, expected.coefficients);
+
+        let lde = extend_trace(&trace::<M>(64), 4, false);
+        let expected = extend_trace(&trace::<F>(64), 4, false);
+        for (column, expected) in lde.evaluations.into_iter().zip(expected.evaluations) {
+            let converted: Vec<F> = column.into_iter().map(F::from).collect();
+            assert_eq!(converted, expected);
+        }
+    }
+
+    /// Fastest of three runs
+    fn time<T>(mut run: impl FnMut() -> T) -> Duration {
+        (0..3)
+            .map(|_| {
+                let start = Instant::now();
+                std::hint::black_box(run());
+                start.elapsed()
+            })
+            .min()
+            .unwrap()
+    }
+
+    #[test]
+    #[ignore = "timing; run with cargo test --release -- --ignored --nocapture"]
+    fn montgomery_speeds_up_multiply_and_lde() {
+        let (a, b) = (
+            sample_coefficients(1 << 14, 1),
+            sample_coefficients(1 << 14, 2),
+        );
+        let (fa, fb) = (polynomial::<F>(&a), polynomial::<F>(&b));
+        let (ma, mb) = (polynomial::<M>(&a), polynomial::<M>(&b));
+        let plain = time(|| fa.multiply(&fb));
+        let montgomery = time(|| ma.multiply(&mb));
+        println!(
+            "Polynomial::multiply, 2^14 coefficients: {:?} plain, {:?} Montgomery ({:.1}x)",
+            plain,
+            montgomery,
+            plain.as_secs_f64() / montgomery.as_secs_f64()
+        );
+        assert!(montgomery < plain);
+
+        let (ft, mt) = (trace::<F>(1 << 14), trace::<M>(1 << 14));
+        let plain = time(|| extend_trace(&ft, 4, false));
+        let montgomery = time(|| extend_trace(&mt, 4, false));
+        println!(
+            "extend_trace, 2^14 rows x 2 columns: {:?} plain, {:?} Montgomery ({:.1}x)",
+            plain,
+            montgomery,
+            plain.as_secs_f64() / montgomery.as_secs_f64()
+        );
+        assert!(montgomery < plain);
+    }
+}
@@ -1,16 +1,14 @@
 use super::polynomial::Polynomial;
-use crate::finite_field::FiniteFieldElement;
-
-impl Polynomial {
-    pub fn div_scalar(&self, scalar: i128) -> Polynomial {
-        let scalar_elem = FiniteFieldElement::new(scalar);
-        let inv = scalar_elem.inverse();
-        let coeffs: Vec<FiniteFieldElement> =
-            self.coefficients.iter().map(|c| c.multiply(inv)).collect();
+use crate::field::Field;
+
+impl<F: Field> Polynomial<F> {
+    pub fn div_scalar(&self, scalar: F) -> Polynomial<F> {
+        let inv = scalar.inverse();
+        let coeffs: Vec<F> = self.coefficients.iter().map(|c| c.multiply(inv)).collect();
         Polynomial::new_ff(coeffs)
     }
 
-    pub fn div(&self, divisor: &Polynomial) -> (Polynomial, Polynomial) {
+    pub fn div(&self, divisor: &Polynomial<F>) -> (Polynomial<F>, Polynomial<F>) {
         // Ensure that the divisor is not zero
         if divisor.coefficients.iter().all(|c| c.is_zero()) {
             panic!("Division by zero");
@@ -25,8 +23,7 @@ impl Polynomial {
 
         // Working copy of dividend coefficients
         let mut dividend = self.coefficients.to_vec();
-        let mut quotient_coeffs: Vec<FiniteFieldElement> =
-            vec![FiniteFieldElement::ZERO; dividend_degree - divisor_degree + 1];
+        let mut quotient_coeffs: Vec<F> = vec![F::ZERO; dividend_degree - divisor_degree + 1];
 
         // Leading coefficient of divisor and its inverse
         let lead_div = divisor.coefficients[divisor_degree];
@@ -61,6 +58,7 @@ impl Polynomial {
 #[cfg(test)]
 mod tests {
     use crate::constants::DEFAULT_FIELD_SIZE;
+    use crate::finite_field::FiniteFieldElement;
 
     use super::*;
 
@@ -70,17 +68,18 @@ mod tests {
         let coeffs = [4_i128, 0_i128, 3_i128].to_vec();
         let poly1 = Polynomial::new(coeffs);
 
-        let divided = poly1.div_scalar(3);
+        let three = FiniteFieldElement::new(3);
+        let divided = poly1.div_scalar(three);
 
         // Check that multiplying back by 3 yields the original polynomial mod p
-        let restored = divided.multiply_scalar(3);
+        let restored = divided.multiply_scalar(three);
         assert_eq!(restored.coefficients.len(), poly1.coefficients.len());
         for (a, b) in restored.coefficients.iter().zip(poly1.coefficients.iter()) {
             assert_eq!(a.value, b.value);
         }
 
         // Also check explicit values against inverse(3)
-        let inv3 = FiniteFieldElement::new(3).inverse().value;
+        let inv3 = three.inverse().value;
         let p = DEFAULT_FIELD_SIZE;
         assert_eq!(divided.coefficients[0].value, (4 * inv3) % p);
         assert_eq!(divided.coefficients[1].value, 0);
@@ -1,27 +1,44 @@
-use crate::finite_field::FiniteFieldElement;
+use crate::extension_field::{BinomialExtension, ExtensionField};
+use crate::field::Field;
 
 use super::polynomial::Polynomial;
 
-impl Polynomial {
-    pub fn evaluate(&self, x: FiniteFieldElement) -> FiniteFieldElement {
-        let mut result = FiniteFieldElement::new_fielded(0, x.field);
+impl<F: Field> Polynomial<F> {
+    pub fn evaluate(&self, x: F) -> F {
+        let mut result = F::ZERO;
         for (i, coeff) in self.coefficients.iter().enumerate() {
-            let coeff_in_x_field = FiniteFieldElement::new_fielded(coeff.value, x.field);
-            let pow = x.pow(i as i128);
-            let multi = pow.multiply(coeff_in_x_field);
+            let pow = x.pow(i as u64);
+            let multi = pow.multiply(*coeff);
             result = result.add(multi);
         }
         result
     }
 
+    /// Evaluate at a point of the degree D extension of F, by Horner's rule; the
+    /// coefficients stay in F, so each step is one extension product
+    pub fn evaluate_extension<const D: usize>(
+        &self,
+        x: ExtensionField<D, F>,
+    ) -> ExtensionField<D, F>
+    where
+        F: BinomialExtension<D>,
+    {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(ExtensionField::ZERO, |acc, &c| {
+                acc.multiply(x).add(ExtensionField::from_base(c))
+            })
+    }
+
     /// Compose the polynomial with another polynomial. For example:
     /// self = f(x) and other = g(x), then this returns f(g(x))
     /// Adjusted from https://github.com/lambdaclass/STARK101-rs/blob/f2bb33501de4ae6006f79b53fa062e11bb0a6288/stark101/src/polynomial.rs#L264
-    pub fn compose(&self, other: Polynomial) -> Polynomial {
+    pub fn compose(&self, other: Polynomial<F>) -> Polynomial<F> {
         // Horner's method: res = 0; for c in self coeffs (high..low): res = other*res + c
-        let mut res = Polynomial::new(vec![]);
+        let mut res = Polynomial::new_ff(vec![]);
         for coef in self.clone().coefficients.into_iter().rev() {
-            res = other.multiply(&res).add(&Polynomial::new(vec![coef.value]));
+            res = other.multiply(&res).add(&Polynomial::new_ff(vec![coef]));
         }
         res
     }
@@ -29,7 +46,7 @@ impl Polynomial {
 
 #[cfg(test)]
 mod tests {
-    use crate::finite_field::FiniteField;
+    use crate::finite_field::FiniteFieldElement;
 
     use super::*;
 
@@ -106,15 +123,15 @@ mod tests {
 
     #[test]
     fn evaluation_overflow() {
-        let pol: Polynomial = Polynomial::new([0_i128, 0, 1].to_vec());
-        let elem = FiniteFieldElement::new_fielded(4, FiniteField::new(10));
-        assert_eq!(pol.evaluate(elem).value, 6);
+        type F10 = FiniteFieldElement<10>;
+        let pol = Polynomial::new_ff([0, 0, 1].map(F10::from_i128).to_vec());
+        assert_eq!(pol.evaluate(F10::from_i128(4)).value, 6);
     }
 
     fn test_polynomial_eval(coeffs: Vec<i128>, value: i128, expected_result: i128) {
         let pol: Polynomial = Polynomial::new(coeffs);
-        let elem = FiniteFieldElement::new_fielded(value, FiniteField::new(i128::MAX));
-        assert_eq!(pol.evaluate(elem).value, expected_result);
+        let elem = FiniteFieldElement::new(value);
+        assert_eq!(pol.evaluate(elem), FiniteFieldElement::new(expected_result));
     }
 
     #[test]
@@ -1,49 +1,52 @@
 use super::polynomial::Polynomial;
-use crate::finite_field::FiniteFieldElement;
+use crate::field::{batch_inverse_skipping_zeros, Field};
 
 /// Lagrange interpolation over a finite field
 ///
 /// Given points (x_i, y_i), returns the unique polynomial P(x)
 /// such that P(x_i) = y_i for all i.
-pub fn lagrange_interpolation(points: &[(i128, i128)]) -> Polynomial {
+pub fn lagrange_interpolation<F: Field>(points: &[(F, F)]) -> Polynomial<F> {
     let n = points.len();
     if n == 0 {
-        return Polynomial::new(vec![]);
+        return Polynomial::new_ff(vec![]);
     }
 
     // Start with the zero polynomial
-    let mut result = Polynomial::new(vec![]);
+    let mut result = Polynomial::new_ff(vec![]);
+
+    // Denominators ∏_{j≠i} (x_i − x_j), inverted together in one batch
+    let mut denominators: Vec<F> = points
+        .iter()
+        .enumerate()
+        .map(|(i, &(xi, _))| {
+            points
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .fold(F::ONE, |denom, (_, &(xj, _))| {
+                    denom.multiply(xi.subtract(xj))
+                })
+        })
+        .collect();
+    batch_inverse_skipping_zeros(&mut denominators);
 
     // Classic Lagrange basis construction over a finite field:
     // P(x) = Σ_i y_i · L_i(x)
     // where L_i(x) = ∏_{j≠i} (x − x_j) / (x_i − x_j)
-    for i in 0..n {
-        let (xi, yi) = points[i];
-
+    for (i, &(_, yi)) in points.iter().enumerate() {
         // Build numerator: ∏_{j≠i} (x − x_j)
         // Using our coeff convention, (x − x_j) is represented as [-x_j, 1]
-        let mut basis = Polynomial::new(vec![1]); // 1 as a polynomial
-        for j in 0..n {
+        let mut basis = Polynomial::new_ff(vec![F::ONE]); // 1 as a polynomial
+        for (j, &(xj, _)) in points.iter().enumerate() {
             if i == j {
                 continue;
             }
-            let xj = points[j].0;
-            basis = basis.multiply(&Polynomial::new(vec![-xj, 1]));
-        }
-
-        // Denominator: ∏_{j≠i} (x_i − x_j) in the field
-        let mut denom = FiniteFieldElement::new(1);
-        for j in 0..n {
-            if i == j {
-                continue;
-            }
-            let xj = points[j].0;
-            denom = denom.multiply(FiniteFieldElement::new(xi - xj));
+            basis = basis.multiply(&Polynomial::new_ff(vec![xj.negate(), F::ONE]));
         }
 
         // Scale basis by y_i * denom^{-1} in the field, then accumulate
-        let scale = FiniteFieldElement::new(yi).multiply(denom.inverse());
-        let scaled = basis.multiply_scalar(scale.value);
+        let scale = yi.multiply(denominators[i]);
+        let scaled = basis.multiply_scalar(scale);
         result = result.add(&scaled);
     }
 
@@ -53,12 +56,23 @@ pub fn lagrange_interpolation(points: &[(i128, i128)]) -> Polynomial {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::constants::DEFAULT_FIELD_SIZE;
+    use crate::finite_field::FiniteFieldElement;
+
+    /// Interpolate integer points in the default field
+    fn interpolate(points: &[(i128, i128)]) -> Polynomial {
+        let points: Vec<(FiniteFieldElement, FiniteFieldElement)> = points
+            .iter()
+            .map(|&(x, y)| (FiniteFieldElement::new(x), FiniteFieldElement::new(y)))
+            .collect();
+        lagrange_interpolation(&points)
+    }
 
     #[test]
     fn lagrange_no_points() {
-        let points = vec![];
+        let points: Vec<(i128, i128)> = vec![];
 
-        let poly = lagrange_interpolation(&points);
+        let poly = interpolate(&points);
 
         assert_eq!(poly.coefficients.len(), 0);
     }
@@ -67,7 +81,7 @@ mod tests {
     fn lagrange_one_point() {
         let points = vec![(1, 2)];
 
-        let poly = lagrange_interpolation(&points);
+        let poly = interpolate(&points);
 
         // f(x) = 2
         // Representation: [2] (constant term only)
@@ -81,13 +95,13 @@ mod tests {
         // Representation: [0, 2]
         let points = vec![(1, 2), (2, 4)];
 
-        let poly = lagrange_interpolation(&points);
+        let poly = interpolate(&points);
         // Expected: 2x (coeffs [0, 2])
         assert_eq!(poly.to_i128_coeffs(), [0, 2]);
 
         // Same result if points are other way around
         let points = vec![(2, 4), (1, 2)];
-        let poly = lagrange_interpolation(&points);
+        let poly = interpolate(&points);
         assert_eq!(poly.to_i128_coeffs(), [0, 2]);
     }
 
@@ -98,8 +112,8 @@ mod tests {
         // Representation: [-2, 5, 3]
         let points = vec![(0, -2), (1, 6), (-5, 48)];
 
-        let poly = lagrange_interpolation(&points);
-        assert_eq!(poly.to_i128_coeffs(), [-2, 5, 3]);
+        let poly = interpolate(&points);
+        assert_eq!(poly.to_i128_coeffs(), [DEFAULT_FIELD_SIZE - 2, 5, 3]);
     }
 
     #[test]
@@ -109,7 +123,7 @@ mod tests {
         // Representation: [0, 0, 1]
         let points = vec![(1, 1), (2, 4), (3, 9)];
 
-        let poly = lagrange_interpolation(&points);
+        let poly = interpolate(&points);
         assert_eq!(poly.to_i128_coeffs(), [0, 0, 1]);
     }
 }
@@ -3,6 +3,8 @@ pub mod divide;
 pub mod evaluate;
 pub mod interpolate;
 pub mod multiply;
+pub mod ntt;
+#[allow(clippy::module_inception)]
 pub mod polynomial;
 pub mod pow;
 pub mod subtract;
@@ -1,27 +1,44 @@
-use crate::finite_field::FiniteFieldElement;
+use crate::field::Field;
 
+use super::ntt::{intt, ntt};
 use super::polynomial::Polynomial;
 
-impl Polynomial {
+/// Both factors need at least this many coefficients before `multiply` uses the NTT
+const NTT_THRESHOLD: usize = 32;
+
+impl<F: Field> Polynomial<F> {
     // Multiply the polynomial by a scalar
-    pub fn multiply_scalar(&self, scalar: i128) -> Polynomial {
-        let scalar_elem = FiniteFieldElement::new(scalar);
-        let coeffs: Vec<FiniteFieldElement> = self
+    pub fn multiply_scalar(&self, scalar: F) -> Polynomial<F> {
+        let coeffs: Vec<F> = self
             .coefficients
             .iter()
-            .map(|coeff| coeff.multiply(scalar_elem))
+            .map(|coeff| coeff.multiply(scalar))
             .collect();
         Polynomial::new_ff(coeffs)
     }
 
-    pub fn multiply(&self, other: &Polynomial) -> Polynomial {
+    /// Multiply two polynomials. Large products go through the NTT, falling back to
+    /// the schoolbook method when the field has no root of unity of the needed order.
+    pub fn multiply(&self, other: &Polynomial<F>) -> Polynomial<F> {
         let a_len = self.coefficients.len();
         let b_len = other.coefficients.len();
         if a_len == 0 || b_len == 0 {
-            return Polynomial::new(vec![]);
+            return Polynomial::new_ff(vec![]);
         }
 
-        let mut result = vec![FiniteFieldElement::ZERO; a_len + b_len - 1];
+        if a_len.min(b_len) >= NTT_THRESHOLD {
+            if let Some(product) = self.multiply_ntt(other) {
+                return product;
+            }
+        }
+        self.multiply_schoolbook(other)
+    }
+
+    /// O(n^2) multiplication
+    fn multiply_schoolbook(&self, other: &Polynomial<F>) -> Polynomial<F> {
+        let a_len = self.coefficients.len();
+        let b_len = other.coefficients.len();
+        let mut result = vec![F::ZERO; a_len + b_len - 1];
 
         for (i, coeff1) in self.coefficients.iter().enumerate() {
             for (j, coeff2) in other.coefficients.iter().enumerate() {
@@ -32,18 +49,43 @@ impl Polynomial {
 
         Polynomial::new_ff(result)
     }
+
+    /// O(n log n) multiplication: evaluate both factors on a large enough subgroup,
+    /// multiply pointwise and interpolate back. None if the field has no such subgroup.
+    fn multiply_ntt(&self, other: &Polynomial<F>) -> Option<Polynomial<F>> {
+        let result_len = self.coefficients.len() + other.coefficients.len() - 1;
+        let n = result_len.next_power_of_two();
+
+        let mut a = self.coefficients.clone();
+        let mut b = other.coefficients.clone();
+        a.resize(n, F::ZERO);
+        b.resize(n, F::ZERO);
+
+        let a_evals = ntt(&a).ok()?;
+        let b_evals = ntt(&b).ok()?;
+        let products: Vec<F> = a_evals
+            .iter()
+            .zip(b_evals.iter())
+            .map(|(x, y)| x.multiply(*y))
+            .collect();
+
+        let mut coefficients = intt(&products).ok()?;
+        coefficients.truncate(result_len);
+        Some(Polynomial::new_ff(coefficients))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::finite_field::FiniteFieldElement;
 
     #[test]
     fn scalar_multiply_empty() {
         // f(x) = 0
         let coeffs = [].to_vec();
         let poly = Polynomial::new(coeffs);
-        let multiplied = poly.multiply_scalar(3);
+        let multiplied = poly.multiply_scalar(FiniteFieldElement::new(3));
 
         assert_eq!(multiplied.coefficients.len(), 0);
     }
@@ -53,7 +95,7 @@ mod tests {
         // f(x) = 3x^2 + 2x + 4
         let coeffs = [4_i128, 2_i128, 3_i128].to_vec();
         let poly = Polynomial::new(coeffs);
-        let multiplied = poly.multiply_scalar(0);
+        let multiplied = poly.multiply_scalar(FiniteFieldElement::new(0));
 
         assert_eq!(multiplied.coefficients.len(), 3);
         assert_eq!(multiplied.coefficients[0].value, 0);
@@ -66,7 +108,7 @@ mod tests {
         // f(x) = 3x^2 + 0x + 4
         let coeffs = [4_i128, 0_i128, 3_i128].to_vec();
         let poly = Polynomial::new(coeffs);
-        let multiplied = poly.multiply_scalar(3);
+        let multiplied = poly.multiply_scalar(FiniteFieldElement::new(3));
 
         assert_eq!(multiplied.coefficients.len(), 3);
         assert_eq!(multiplied.coefficients[0].value, 12);
@@ -74,6 +116,32 @@ mod tests {
         assert_eq!(multiplied.coefficients[2].value, 9);
     }
 
+    #[test]
+    fn ntt_multiply_matches_schoolbook() {
+        let a = Polynomial::new((0..40).map(|i| i * 31 - 7).collect());
+        let b = Polynomial::new((0..50).map(|i| 1000 - i * i).collect());
+
+        let product = a.multiply(&b);
+        let expected = a.multiply_schoolbook(&b);
+        assert_eq!(product.coefficients.len(), 89);
+        assert_eq!(product.coefficients, expected.coefficients);
+        assert_eq!(
+            a.multiply_ntt(&b).unwrap().coefficients,
+            expected.coefficients
+        );
+    }
+
+    #[test]
+    fn ntt_multiply_falls_back_in_small_fields() {
+        // p = 17 has no root of unity of order 128, so the product is schoolbook
+        type F17 = FiniteFieldElement<17>;
+        let a = Polynomial::new_ff((0..40).map(F17::from_i128).collect());
+        let b = Polynomial::new_ff((1..51).map(F17::from_i128).collect());
+
+        assert!(a.multiply_ntt(&b).is_none());
+        assert_eq!(a.multiply(&b).coefficients.len(), 89);
+    }
+
     #[test]
     fn multiply5() {
         // f(x) = 3x^2 + 0x + 4
@@ -0,0 +1,192 @@
+use core::fmt;
+
+use crate::field::Field;
+
+/// Why a vector cannot be transformed with a radix-2 NTT
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NttError {
+    /// Radix-2 NTT needs a power-of-two length
+    LengthNotPowerOfTwo(usize),
+    /// The field has no root of unity of order `length`
+    ExceedsTwoAdicity { length: usize, two_adicity: u32 },
+}
+
+impl fmt::Display for NttError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NttError::LengthNotPowerOfTwo(n) => {
+                write!(f, "NTT length {} is not a power of two", n)
+            }
+            NttError::ExceedsTwoAdicity {
+                length,
+                two_adicity,
+            } => write!(
+                f,
+                "NTT length {} exceeds the field's two-adic subgroup of order 2^{}",
+                length, two_adicity
+            ),
+        }
+    }
+}
+
+impl std::error::Error for NttError {}
+
+/// Primitive root of unity of order `n`, if `n` is a power of two the field supports
+fn root_for_length<F: Field>(n: usize) -> Result<F, NttError> {
+    if !n.is_power_of_two() {
+        return Err(NttError::LengthNotPowerOfTwo(n));
+    }
+    let two_adicity = F::TWO_ADICITY;
+    if n.trailing_zeros() > two_adicity {
+        return Err(NttError::ExceedsTwoAdicity {
+            length: n,
+            two_adicity,
+        });
+    }
+    Ok(F::primitive_root_of_unity(n).expect("power of two within the two-adicity divides p - 1"))
+}
+
+/// Iterative radix-2 Cooley–Tukey transform: values[i] becomes Σ_j values[j] · root^(i·j)
+fn transform_in_place<F: Field>(values: &mut [F], root: F) {
+    let n = values.len();
+    if n <= 1 {
+        return;
+    }
+
+    // Bit-reversal permutation
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+
+    // Butterflies, doubling the block length every round
+    let mut len = 2;
+    while len <= n {
+        let step = root.pow((n / len) as u64);
+        for start in (0..n).step_by(len) {
+            let mut w = F::ONE;
+            for j in 0..len / 2 {
+                let u = values[start + j];
+                let v = values[start + j + len / 2].multiply(w);
+                values[start + j] = u.add(v);
+                values[start + j + len / 2] = u.subtract(v);
+                w = w.multiply(step);
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// Forward NTT: evaluations of the polynomial with `coefficients` at ω^0, ..., ω^(n-1),
+/// for ω the primitive n-th root of unity of the field
+pub fn ntt<F: Field>(coefficients: &[F]) -> Result<Vec<F>, NttError> {
+    let root = root_for_length(coefficients.len())?;
+    let mut values = coefficients.to_vec();
+    transform_in_place(&mut values, root);
+    Ok(values)
+}
+
+/// Inverse NTT: coefficients of the polynomial taking `evaluations[i]` at ω^i
+pub fn intt<F: Field>(evaluations: &[F]) -> Result<Vec<F>, NttError> {
+    let root: F = root_for_length(evaluations.len())?;
+    let mut values = evaluations.to_vec();
+    transform_in_place(&mut values, root.inverse());
+    let n_inv = F::from_u64(evaluations.len() as u64).inverse();
+    Ok(values.into_iter().map(|v| v.multiply(n_inv)).collect())
+}
+
+/// Coset NTT: evaluations at h·ω^0, ..., h·ω^(n-1) for h = `offset`, computed as the
+/// NTT of the coefficients scaled by h^j
+pub fn coset_ntt<F: Field>(coefficients: &[F], offset: F) -> Result<Vec<F>, NttError> {
+    let mut power = F::ONE;
+    let scaled: Vec<F> = coefficients
+        .iter()
+        .map(|c| {
+            let scaled = c.multiply(power);
+            power = power.multiply(offset);
+            scaled
+        })
+        .collect();
+    ntt(&scaled)
+}
+
+/// Inverse coset NTT: coefficients of the polynomial taking `evaluations[i]` at h·ω^i
+pub fn coset_intt<F: Field>(evaluations: &[F], offset: F) -> Result<Vec<F>, NttError> {
+    let scaled = intt(evaluations)?;
+    let offset_inv = offset.inverse();
+    let mut power = F::ONE;
+    Ok(scaled
+        .into_iter()
+        .map(|c| {
+            let coefficient = c.multiply(power);
+            power = power.multiply(offset_inv);
+            coefficient
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluation_domain::EvaluationDomain;
+    use crate::finite_field::FiniteFieldElement;
+    use crate::polynomial::polynomial::Polynomial;
+
+    type F = FiniteFieldElement;
+
+    #[test]
+    fn ntt_matches_naive_evaluation() {
+        let coefficients: Vec<F> = [3, -1, 4, 1, 5, 9, 2, 6].map(F::new).to_vec();
+        let poly = Polynomial::new_ff(coefficients.clone());
+        let root = F::primitive_root_of_unity(8).unwrap();
+
+        let evaluations = ntt(&coefficients).unwrap();
+        for (i, value) in evaluations.iter().enumerate() {
+            assert_eq!(*value, poly.evaluate(root.pow(i as u64)));
+        }
+    }
+
+    #[test]
+    fn intt_inverts_ntt() {
+        for log_n in 0..8 {
+            let coefficients: Vec<F> = (0..1 << log_n).map(|i| F::new(i * i + 7)).collect();
+            let evaluations = ntt(&coefficients).unwrap();
+            assert_eq!(intt(&evaluations).unwrap(), coefficients);
+        }
+    }
+
+    #[test]
+    fn coset_ntt_evaluates_on_coset() {
+        let offset = F::multiplicative_generator();
+        let coefficients: Vec<F> = (0..16).map(|i| F::new(3 * i + 1)).collect();
+        let poly = Polynomial::new_ff(coefficients.clone());
+        let coset = EvaluationDomain::new_coset(16, offset);
+
+        let evaluations = coset_ntt(&coefficients, offset).unwrap();
+        for (i, value) in evaluations.iter().enumerate() {
+            assert_eq!(*value, poly.evaluate(coset.element(i)));
+        }
+        assert_eq!(coset_intt(&evaluations, offset).unwrap(), coefficients);
+    }
+
+    #[test]
+    fn ntt_rejects_unsupported_lengths() {
+        let values = vec![F::ONE; 6];
+        assert_eq!(ntt(&values), Err(NttError::LengthNotPowerOfTwo(6)));
+        assert_eq!(intt::<F>(&[]), Err(NttError::LengthNotPowerOfTwo(0)));
+
+        // p = 17 has a two-adic subgroup of order 16 only
+        type F17 = FiniteFieldElement<17>;
+        assert!(ntt(&[F17::ONE; 16]).is_ok());
+        assert_eq!(
+            ntt(&[F17::ONE; 32]),
+            Err(NttError::ExceedsTwoAdicity {
+                length: 32,
+                two_adicity: 4
+            })
+        );
+    }
+}
@@ -1,13 +1,14 @@
+use crate::field::Field;
 use crate::finite_field::FiniteFieldElement;
 use core::fmt;
 
 #[derive(Debug, Clone)]
-pub struct Polynomial {
-    pub coefficients: Vec<FiniteFieldElement>,
+pub struct Polynomial<F: Field = FiniteFieldElement> {
+    pub coefficients: Vec<F>,
 }
 
 impl Polynomial {
-    /// Construct from raw i128 coefficients, mapping into the finite field
+    /// Construct from raw i128 coefficients in the default field
     pub fn new(coefficients: Vec<i128>) -> Self {
         Polynomial {
             coefficients: coefficients
@@ -16,9 +17,18 @@ impl Polynomial {
                 .collect(),
         }
     }
+}
+
+impl<const P: i128> Polynomial<FiniteFieldElement<P>> {
+    /// Convenience: export coefficients as i128 values
+    pub fn to_i128_coeffs(&self) -> Vec<i128> {
+        self.coefficients.iter().map(|c| c.value).collect()
+    }
+}
 
+impl<F: Field> Polynomial<F> {
     /// Construct from finite field elements
-    pub fn new_ff(coefficients: Vec<FiniteFieldElement>) -> Self {
+    pub fn new_ff(coefficients: Vec<F>) -> Self {
         Polynomial { coefficients }
     }
 
@@ -33,13 +43,12 @@ impl Polynomial {
     }
 
     /// Returns a polynomial where all coefficients are zero except the highest term
-    pub fn leading_term(&self) -> Polynomial {
+    pub fn leading_term(&self) -> Polynomial<F> {
         let highest_degree_index = self.coefficients.len() - 1;
 
         // Create a new vector to store the coefficients of the highest degree term
-        let mut highest_degree_coefficients =
-            vec![FiniteFieldElement::new(0); highest_degree_index];
-        highest_degree_coefficients.push(self.coefficients[highest_degree_index].clone());
+        let mut highest_degree_coefficients = vec![F::ZERO; highest_degree_index];
+        highest_degree_coefficients.push(self.coefficients[highest_degree_index]);
 
         // Create a new polynomial with the highest degree term
         Polynomial {
@@ -48,7 +57,7 @@ impl Polynomial {
     }
 
     /// Remove all zero coefficients from the end
-    pub fn trim(&self) -> Polynomial {
+    pub fn trim(&self) -> Polynomial<F> {
         // Find the index of the last non-zero coefficient from the end
         let end_index = self
             .coefficients
@@ -61,23 +70,18 @@ impl Polynomial {
             coefficients: self.coefficients[..end_index].to_vec(),
         }
     }
-
-    /// Convenience: export coefficients as i128 values
-    pub fn to_i128_coeffs(&self) -> Vec<i128> {
-        self.coefficients.iter().map(|c| c.value).collect()
-    }
 }
 
-impl fmt::Display for Polynomial {
+impl<F: Field> fmt::Display for Polynomial<F> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut terms = Vec::new();
         for (i, coeff) in self.coefficients.iter().enumerate() {
             if !coeff.is_zero() {
                 let degree = i;
                 let term_str = match degree {
-                    0 => format!("{}", coeff.value),
-                    1 => format!("{}x", coeff.value),
-                    _ => format!("{}x^{}", coeff.value, degree),
+                    0 => format!("{}", coeff),
+                    1 => format!("{}x", coeff),
+                    _ => format!("{}x^{}", coeff, degree),
                 };
                 terms.push(term_str);
             }
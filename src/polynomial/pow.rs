@@ -1,13 +1,14 @@
 use super::polynomial::Polynomial;
+use crate::field::Field;
 
-impl Polynomial {
+impl<F: Field> Polynomial<F> {
     // From ChatGPT
     pub fn pow(&self, other: i128) -> Self {
         if other == 0 {
             // If the exponent is 0, return the identity polynomial, which is the polynomial representing the constant term 1.
-            Polynomial::new(vec![1])
+            Polynomial::new_ff(vec![F::ONE])
         } else {
-            let mut res = Polynomial::new(vec![1]);
+            let mut res = Polynomial::new_ff(vec![F::ONE]);
             let mut current = self.clone();
             let mut exponent = other;
             while exponent > 0 {
@@ -1,22 +1,20 @@
 use super::polynomial::Polynomial;
-use crate::finite_field::FiniteFieldElement;
+use crate::field::Field;
 
-impl Polynomial {
-    pub fn sub(&self, other: &Polynomial) -> Polynomial {
+impl<F: Field> Polynomial<F> {
+    pub fn sub(&self, other: &Polynomial<F>) -> Polynomial<F> {
         let a_len = self.coefficients.len();
         let b_len = other.coefficients.len();
         let max_len = if a_len > b_len { a_len } else { b_len };
 
-        let mut result_coeffs: Vec<FiniteFieldElement> = vec![FiniteFieldElement::ZERO; max_len];
+        let mut result_coeffs: Vec<F> = vec![F::ZERO; max_len];
 
         // Copy the original
-        for i in 0..self.coefficients.len() {
-            result_coeffs[i] = self.coefficients[i];
-        }
+        result_coeffs[..a_len].copy_from_slice(&self.coefficients);
 
         // Subtract other in the field
-        for i in 0..other.coefficients.len() {
-            result_coeffs[i] = result_coeffs[i].subtract(other.coefficients[i]);
+        for (result, coeff) in result_coeffs.iter_mut().zip(other.coefficients.iter()) {
+            *result = result.subtract(*coeff);
         }
 
         Polynomial::new_ff(result_coeffs).trim()
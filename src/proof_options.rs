@@ -0,0 +1,399 @@
+use core::fmt;
+
+use crate::constants::{
+    DEFAULT_BLOWUP_FACTOR, DEFAULT_FRI_FOLDING_FACTOR, DEFAULT_FRI_MAX_REMAINDER_DEGREE,
+    DEFAULT_GRINDING_BITS, DEFAULT_NUM_QUERIES,
+};
+use crate::fiat_shamir::Transcript;
+use crate::field::Field;
+use crate::finite_field::is_prime;
+use crate::hashing::Hasher;
+
+/// Largest supported proof-of-work difficulty; grinding cost doubles with every bit
+pub const MAX_GRINDING_BITS: u32 = 32;
+
+/// Parameters of a proof, chosen by the prover and carried in the proof so the
+/// verifier checks it under the same parameters. Trades proof size and prover
+/// time against security. The field is not an option: it is the type parameter
+/// of the prover and verifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofOptions {
+    /// Size of the extended domain over the trace domain (a power of two)
+    pub blowup_factor: usize,
+    /// Number of query points opened in the extended domain
+    pub num_queries: usize,
+    /// Domain reduction of each FRI round; only 2 is supported
+    pub fri_folding_factor: usize,
+    /// Largest degree of the FRI final layer, which is sent in the clear
+    pub fri_max_remainder_degree: usize,
+    /// Proof-of-work difficulty before the queries are drawn
+    pub grinding_bits: u32,
+}
+
+/// Why a set of proof options cannot be used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofOptionsError {
+    /// The blowup factor must be a power of two, at least 2
+    InvalidBlowupFactor(usize),
+    /// The blowup factor must exceed the constraint degree, or the composition
+    /// polynomial does not fit in the extended domain
+    BlowupTooSmall {
+        blowup: usize,
+        constraint_degree: usize,
+    },
+    /// At least one query is needed
+    NoQueries,
+    /// FRI only folds by 2
+    UnsupportedFoldingFactor(usize),
+    /// The FRI final layer size `(d + 1).next_power_of_two() · blowup` overflows
+    RemainderDegreeTooLarge(usize),
+    /// The final layer would hold the whole extended domain: no FRI round is left
+    RemainderTooLarge {
+        final_layer_size: usize,
+        lde_size: usize,
+    },
+    /// More grinding bits than `MAX_GRINDING_BITS`
+    TooManyGrindingBits(u32),
+    /// The field characteristic is not an odd prime
+    InvalidField(u64),
+    /// The field has no subgroup as large as the extended domain
+    DomainTooLarge { lde_size: usize, two_adicity: u32 },
+    /// trace_length · blowup_factor overflows
+    DomainSizeOverflow { trace_length: usize, blowup: usize },
+}
+
+impl fmt::Display for ProofOptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProofOptionsError::InvalidBlowupFactor(b) => {
+                write!(f, "blowup factor {} is not a power of two >= 2", b)
+            }
+            ProofOptionsError::BlowupTooSmall {
+                blowup,
+                constraint_degree,
+            } => write!(
+                f,
+                "blowup factor {} must exceed the constraint degree {}",
+                blowup, constraint_degree
+            ),
+            ProofOptionsError::NoQueries => write!(f, "at least one query is needed"),
+            ProofOptionsError::UnsupportedFoldingFactor(k) => {
+                write!(f, "FRI folding factor {} is not supported (only 2)", k)
+            }
+            ProofOptionsError::RemainderDegreeTooLarge(d) => write!(
+                f,
+                "FRI remainder degree {} is too large for any final layer",
+                d
+            ),
+            ProofOptionsError::RemainderTooLarge {
+                final_layer_size,
+                lde_size,
+            } => write!(
+                f,
+                "FRI final layer of {} values leaves no round for an extended domain of {}",
+                final_layer_size, lde_size
+            ),
+            ProofOptionsError::TooManyGrindingBits(bits) => write!(
+                f,
+                "{} grinding bits exceed the maximum of {}",
+                bits, MAX_GRINDING_BITS
+            ),
+            ProofOptionsError::InvalidField(p) => {
+                write!(f, "field characteristic {} is not an odd prime", p)
+            }
+            ProofOptionsError::DomainTooLarge {
+                lde_size,
+                two_adicity,
+            } => write!(
+                f,
+                "extended domain of {} points needs more than the field's 2^{} subgroup",
+                lde_size, two_adicity
+            ),
+            ProofOptionsError::DomainSizeOverflow {
+                trace_length,
+                blowup,
+            } => write!(
+                f,
+                "extended domain of {} rows · blowup {} does not fit in usize",
+                trace_length, blowup
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ProofOptionsError {}
+
+impl Default for ProofOptions {
+    fn default() -> Self {
+        ProofOptions {
+            blowup_factor: DEFAULT_BLOWUP_FACTOR,
+            num_queries: DEFAULT_NUM_QUERIES,
+            fri_folding_factor: DEFAULT_FRI_FOLDING_FACTOR,
+            fri_max_remainder_degree: DEFAULT_FRI_MAX_REMAINDER_DEGREE,
+            grinding_bits: DEFAULT_GRINDING_BITS,
+        }
+    }
+}
+
+impl ProofOptions {
+    /// Size of the FRI final layer: the evaluations of a polynomial of degree at
+    /// most `fri_max_remainder_degree`, kept at the blowup factor. Fails if that
+    /// does not fit in usize.
+    pub fn fri_final_layer_size(&self) -> Result<usize, ProofOptionsError> {
+        self.fri_max_remainder_degree
+            .checked_add(1)
+            .and_then(usize::checked_next_power_of_two)
+            .and_then(|degrees| degrees.checked_mul(self.blowup_factor))
+            .ok_or(ProofOptionsError::RemainderDegreeTooLarge(
+                self.fri_max_remainder_degree,
+            ))
+    }
+
+    /// Check the options on their own
+    pub fn validate(&self) -> Result<(), ProofOptionsError> {
+        if self.blowup_factor < 2 || !self.blowup_factor.is_power_of_two() {
+            return Err(ProofOptionsError::InvalidBlowupFactor(self.blowup_factor));
+        }
+        if self.num_queries == 0 {
+            return Err(ProofOptionsError::NoQueries);
+        }
+        if self.fri_folding_factor != 2 {
+            return Err(ProofOptionsError::UnsupportedFoldingFactor(
+                self.fri_folding_factor,
+            ));
+        }
+        if self.grinding_bits > MAX_GRINDING_BITS {
+            return Err(ProofOptionsError::TooManyGrindingBits(self.grinding_bits));
+        }
+        self.fri_final_layer_size()?;
+        Ok(())
+    }
+
+    /// Check the options for a trace over `F` of `trace_length` rows and
+    /// transition constraints of degree at most `constraint_degree`
+    pub fn validate_for<F: Field>(
+        &self,
+        trace_length: usize,
+        constraint_degree: usize,
+    ) -> Result<(), ProofOptionsError> {
+        self.validate()?;
+        let prime = F::CHARACTERISTIC;
+        if prime < 3 || !is_prime(prime) {
+            return Err(ProofOptionsError::InvalidField(prime));
+        }
+        if constraint_degree >= self.blowup_factor {
+            return Err(ProofOptionsError::BlowupTooSmall {
+                blowup: self.blowup_factor,
+                constraint_degree,
+            });
+        }
+        let lde_size = trace_length.checked_mul(self.blowup_factor).ok_or(
+            ProofOptionsError::DomainSizeOverflow {
+                trace_length,
+                blowup: self.blowup_factor,
+            },
+        )?;
+        let two_adicity = F::TWO_ADICITY;
+        if lde_size.trailing_zeros() > two_adicity {
+            return Err(ProofOptionsError::DomainTooLarge {
+                lde_size,
+                two_adicity,
+            });
+        }
+        let final_layer_size = self.fri_final_layer_size()?;
+        if final_layer_size >= lde_size {
+            return Err(ProofOptionsError::RemainderTooLarge {
+                final_layer_size,
+                lde_size,
+            });
+        }
+        Ok(())
+    }
+
+    /// Bind the options into the transcript, so challenges depend on them
+    pub fn absorb_into<H: Hasher>(&self, transcript: &mut Transcript<H>) {
+        transcript.append_u64(b"blowup_factor", self.blowup_factor as u64);
+        transcript.append_u64(b"num_queries", self.num_queries as u64);
+        transcript.append_u64(b"fri_folding_factor", self.fri_folding_factor as u64);
+        transcript.append_u64(
+            b"fri_max_remainder_degree",
+            self.fri_max_remainder_degree as u64,
+        );
+        transcript.append_u64(b"grinding_bits", self.grinding_bits as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::finite_field::FiniteFieldElement;
+
+    type F = FiniteFieldElement;
+
+    #[test]
+    fn default_matches_the_constants() {
+        let options = ProofOptions::default();
+        assert_eq!(options.validate(), Ok(()));
+        assert_eq!(options.validate_for::<F>(8, 2), Ok(()));
+        assert_eq!(options.blowup_factor, 4);
+        assert_eq!(options.num_queries, 5);
+        assert_eq!(options.fri_final_layer_size(), Ok(4));
+    }
+
+    #[test]
+    fn final_layer_size_rounds_up() {
+        let options = ProofOptions {
+            blowup_factor: 8,
+            fri_max_remainder_degree: 2,
+            ..ProofOptions::default()
+        };
+        assert_eq!(options.fri_final_layer_size(), Ok(32));
+    }
+
+    #[test]
+    fn rejects_bad_options() {
+        let base = ProofOptions::default();
+        let cases = [
+            (
+                ProofOptions {
+                    blowup_factor: 6,
+                    ..base
+                },
+                ProofOptionsError::InvalidBlowupFactor(6),
+            ),
+            (
+                ProofOptions {
+                    blowup_factor: 1,
+                    ..base
+                },
+                ProofOptionsError::InvalidBlowupFactor(1),
+            ),
+            (
+                ProofOptions {
+                    num_queries: 0,
+                    ..base
+                },
+                ProofOptionsError::NoQueries,
+            ),
+            (
+                ProofOptions {
+                    fri_folding_factor: 4,
+                    ..base
+                },
+                ProofOptionsError::UnsupportedFoldingFactor(4),
+            ),
+            (
+                ProofOptions {
+                    grinding_bits: 33,
+                    ..base
+                },
+                ProofOptionsError::TooManyGrindingBits(33),
+            ),
+        ];
+        for (options, error) in cases {
+            assert_eq!(options.validate(), Err(error));
+            assert_eq!(options.validate_for::<F>(8, 1), Err(error));
+        }
+
+        // 91 = 7 · 13 is a modulus, but not a field
+        assert_eq!(
+            base.validate_for::<FiniteFieldElement<91>>(8, 1),
+            Err(ProofOptionsError::InvalidField(91))
+        );
+    }
+
+    #[test]
+    fn rejects_options_that_do_not_fit_the_statement() {
+        let options = ProofOptions::default();
+        assert_eq!(
+            options.validate_for::<F>(8, 4),
+            Err(ProofOptionsError::BlowupTooSmall {
+                blowup: 4,
+                constraint_degree: 4
+            })
+        );
+
+        // p - 1 = 96 = 2^5 · 3: no subgroup of order 64
+        type F97 = FiniteFieldElement<97>;
+        assert_eq!(options.validate_for::<F97>(8, 1), Ok(()));
+        assert_eq!(
+            options.validate_for::<F97>(16, 1),
+            Err(ProofOptionsError::DomainTooLarge {
+                lde_size: 64,
+                two_adicity: 5
+            })
+        );
+
+        let big_remainder = ProofOptions {
+            fri_max_remainder_degree: 7,
+            ..options
+        };
+        assert_eq!(
+            big_remainder.validate_for::<F>(8, 1),
+            Err(ProofOptionsError::RemainderTooLarge {
+                final_layer_size: 32,
+                lde_size: 32
+            })
+        );
+        assert_eq!(big_remainder.validate_for::<F>(16, 1), Ok(()));
+    }
+
+    #[test]
+    fn oversized_options_are_errors_not_overflows() {
+        let base = ProofOptions::default();
+        let huge_remainder = ProofOptions {
+            fri_max_remainder_degree: usize::MAX,
+            ..base
+        };
+        let error = ProofOptionsError::RemainderDegreeTooLarge(usize::MAX);
+        assert_eq!(huge_remainder.fri_final_layer_size(), Err(error));
+        assert_eq!(huge_remainder.validate(), Err(error));
+        assert_eq!(huge_remainder.validate_for::<F>(8, 1), Err(error));
+        // Fits before the blowup multiplies it
+        let remainder = ProofOptions {
+            fri_max_remainder_degree: 1 << 62,
+            ..base
+        };
+        assert_eq!(
+            remainder.validate(),
+            Err(ProofOptionsError::RemainderDegreeTooLarge(1 << 62))
+        );
+
+        let huge_blowup = ProofOptions {
+            blowup_factor: 1 << 62,
+            ..base
+        };
+        assert_eq!(
+            huge_blowup.validate_for::<F>(8, 1),
+            Err(ProofOptionsError::DomainSizeOverflow {
+                trace_length: 8,
+                blowup: 1 << 62
+            })
+        );
+    }
+
+    #[test]
+    fn options_drive_the_transcript() {
+        let challenge = |options: ProofOptions| {
+            let mut t: Transcript = Transcript::new(b"options-test");
+            options.absorb_into(&mut t);
+            t.challenge::<F>(b"c")
+        };
+        let base = ProofOptions::default();
+        assert_eq!(challenge(base), challenge(base));
+        assert_ne!(
+            challenge(base),
+            challenge(ProofOptions {
+                num_queries: 6,
+                ..base
+            })
+        );
+        assert_ne!(
+            challenge(base),
+            challenge(ProofOptions {
+                fri_max_remainder_degree: 1,
+                ..base
+            })
+        );
+    }
+}
@@ -1,19 +1,39 @@
-use crate::constants::EXTENSION_FACTOR;
+use crate::air::fibonacci::FibonacciAir;
+use crate::air::{combine_constraints, Air};
+use crate::constants::TARGET_SECURITY_BITS;
 use crate::evaluation_domain::EvaluationDomain;
-use crate::finite_field::{FiniteField, FiniteFieldElement};
-use crate::fri::fold_once;
-use crate::merkle_tree::MerkleTree;
-use crate::polynomial::interpolate::lagrange_interpolation;
+use crate::field::{batch_inverse, Field};
+use crate::finite_field::FiniteFieldElement;
+use crate::fri::FriProver;
+use crate::hashing::{digest_hex, Hasher};
+use crate::merkle_tree::{MerkleProof, MerkleTree};
+use crate::polynomial::ntt::{coset_ntt, intt};
 use crate::polynomial::polynomial::Polynomial;
+use crate::proof_options::ProofOptions;
+use crate::security::estimate_protocol_security;
 use crate::trace::Trace;
-use crate::verifier::{derive_fri_betas_from_commitment, SamplingData, StarkProof};
+use crate::verifier::{
+    derive_constraint_coefficients, derive_quotient_coefficients, derive_sample_points,
+    evaluate_transition_vanishing, extended_domain, next_index, proof_security,
+    protocol_transcript, trace_domain, SamplingData, StarkProof,
+};
+
+/// Low Degree Extension of a trace
+pub struct LowDegreeExtension<F: Field = FiniteFieldElement> {
+    /// Column-major evaluations over the extended domain
+    pub evaluations: Vec<Vec<F>>,
+    /// Column polynomials in coefficient form, if requested
+    pub coefficients: Option<Vec<Polynomial<F>>>,
+}
 
-/// Low Degree Extension: Interpolate trace columns and evaluate at larger domain
-pub fn extend_trace(
-    trace: &Trace,
-    field: FiniteField,
+/// Low Degree Extension: an inverse NTT over the trace domain gives each column's
+/// coefficients, a coset NTT evaluates them over the larger extended domain.
+/// O(N log N) per column.
+pub fn extend_trace<F: Field>(
+    trace: &Trace<F>,
     extension_factor: usize,
-) -> Vec<Vec<FiniteFieldElement>> {
+    keep_coefficients: bool,
+) -> LowDegreeExtension<F> {
     println!("🔄 Performing Low Degree Extension...");
 
     let original_size = trace.num_rows();
@@ -25,282 +45,371 @@ pub fn extend_trace(
         extended_size, extension_factor
     );
 
-    // Create evaluation domain for the extended size
-    let eval_domain = EvaluationDomain::new_linear(field, extended_size);
-
-    // For each column in the trace, interpolate and extend
-    let mut extended_trace = Vec::new();
+    // Row i of the trace sits at g^i; the extended domain is a coset of the
+    // subgroup of order extended_size, disjoint from the trace domain
+    let offset = F::multiplicative_generator();
+    let mut evaluations = Vec::with_capacity(trace.num_columns());
+    let mut coefficients = Vec::new();
 
     for col in 0..trace.num_columns() {
         println!("   Extending column {}...", col);
 
-        // Get the original column values
-        let original_column = trace.get_column(col);
+        let column = trace.get_column(col);
 
-        // Create interpolation points: (step, value) pairs
-        let mut points = Vec::new();
-        for (step, &value) in original_column.iter().enumerate() {
-            points.push((step as i128, value));
-        }
+        // Interpolate over the trace domain
+        let mut column_coefficients = intt(&column).unwrap_or_else(|e| panic!("LDE failed: {}", e));
 
-        // Interpolate to get polynomial
-        let poly = lagrange_interpolation(&points);
+        // Evaluate over the extended domain
+        column_coefficients.resize(extended_size, F::ZERO);
+        let extended_column =
+            coset_ntt(&column_coefficients, offset).unwrap_or_else(|e| panic!("LDE failed: {}", e));
+        evaluations.push(extended_column);
 
-        // Evaluate polynomial at extended domain
-        let mut extended_column = Vec::new();
-        for i in 0..extended_size {
-            let point = eval_domain.element(i);
-            let value = poly.evaluate(point);
-            extended_column.push(value);
+        if keep_coefficients {
+            column_coefficients.truncate(original_size);
+            coefficients.push(Polynomial::new_ff(column_coefficients));
         }
-
-        extended_trace.push(extended_column);
     }
 
     println!("   ✅ LDE complete!");
-    extended_trace
-}
-
-/// Create constraint polynomial: C(x) = F(x) - F(x-1) - F(x-2)
-/// This polynomial should evaluate to 0 at all valid computation steps
-fn create_fibonacci_constraint_poly(
-    trace: &Trace,
-    field: FiniteField,
-) -> (Polynomial, EvaluationDomain) {
-    println!("🔧 Creating Fibonacci constraint polynomial...");
-
-    let original_size = trace.num_rows();
-    let eval_domain = EvaluationDomain::new_linear(field, original_size);
-
-    // Create polynomials for each column: F(x-2), F(x-1), F(x)
-    let mut column_polys = Vec::new();
-
-    for col in 0..trace.num_columns() {
-        let column_values = trace.get_column(col);
-        let mut points = Vec::new();
-        for (step, &value) in column_values.iter().enumerate() {
-            points.push((step as i128, value));
-        }
-        let poly = lagrange_interpolation(&points);
-        column_polys.push(poly);
-    }
-
-    // C(x) = F(x) - F(x-1) - F(x-2)
-    // For this simplified version, we'll create a constraint polynomial
-    // that evaluates to 0 at all points where the Fibonacci rule should hold
-
-    // Create a polynomial that represents the constraint residuals
-    let mut constraint_points = Vec::new();
-
-    // For steps 0 and 1, the constraint is trivially satisfied (no previous terms)
-    constraint_points.push((0, 0));
-    if original_size > 1 {
-        constraint_points.push((1, 0));
+    LowDegreeExtension {
+        evaluations,
+        coefficients: keep_coefficients.then_some(coefficients),
     }
+}
 
-    // For steps 2 and beyond, compute the actual constraint residual
-    for step in 2..original_size {
-        let f_n_minus_2 = trace.get(step, 0).unwrap();
-        let f_n_minus_1 = trace.get(step, 1).unwrap();
-        let f_n = trace.get(step, 2).unwrap();
-        let residual = f_n - f_n_minus_1 - f_n_minus_2;
-        constraint_points.push((step as i128, residual));
-    }
+/// Evaluate the composition polynomial C(x) = Σ_j α_j · C_j(P(x), P(g · x)), where P
+/// are the trace column polynomials, at every point of the extended domain, using the
+/// LDE rows at x and g · x (see `next_index`). C vanishes on the transition domain if
+/// and only if every transition constraint holds.
+fn evaluate_composition<A: Air>(
+    air: &A,
+    extended_trace: &[Vec<A::Field>],
+    blowup_factor: usize,
+    alphas: &[A::Field],
+) -> Vec<A::Field> {
+    println!("🔧 Evaluating composition polynomial from AIR constraints...");
 
-    // Interpolate the constraint residuals to get the constraint polynomial
-    let constraint_poly = lagrange_interpolation(&constraint_points);
+    let extended_size = extended_trace[0].len();
+    let row = |i: usize| -> Vec<A::Field> { extended_trace.iter().map(|col| col[i]).collect() };
+
+    // C has degree below N (checked by `validate_for`), so its N evaluations
+    // determine it and it never needs to be interpolated
+    let constraint_values: Vec<A::Field> = (0..extended_size)
+        .map(|i| {
+            let next = next_index(i, extended_size, blowup_factor);
+            let residuals = air.evaluate_transition(&row(i), &row(next));
+            combine_constraints(&residuals, alphas)
+        })
+        .collect();
 
     println!(
-        "   ✅ Constraint polynomial created (degree: {})",
-        constraint_poly.degree()
+        "   ✅ Composition polynomial evaluated at {} points",
+        constraint_values.len()
     );
-
-    (constraint_poly, eval_domain)
+    constraint_values
 }
 
-/// Create the vanishing polynomial Z_H(x) = ∏(x - a_i) for domain H
-fn create_vanishing_polynomial(domain: &EvaluationDomain) -> Polynomial {
-    println!("🔧 Creating vanishing polynomial...");
-
-    let mut result = Polynomial::new(vec![1]); // Start with 1
+/// Check the transition constraints directly on the trace (prover-side sanity check)
+fn check_transition_constraints<A: Air>(air: &A, trace: &Trace<A::Field>) {
+    for (step, rows) in trace.trace.windows(2).enumerate() {
+        let residuals = air.evaluate_transition(&rows[0], &rows[1]);
+        if residuals.iter().any(|r| !r.is_zero()) {
+            println!(
+                "   ⚠️  Transition constraint violated between rows {} and {}",
+                step,
+                step + 1
+            );
+        }
+    }
+}
 
-    for &point in &domain.points {
-        // Multiply by (x - point) = [negated_point, 1]
-        let negated_point = point.negate();
-        let linear_factor = Polynomial::new_ff(vec![
-            negated_point,
-            FiniteFieldElement::new_fielded(1, point.field),
-        ]);
-        result = result.multiply(&linear_factor);
+/// Check the boundary constraints directly on the trace (prover-side sanity check)
+fn check_boundary_constraints<A: Air>(air: &A, trace: &Trace<A::Field>) {
+    for c in air.boundary_constraints() {
+        if trace.get(c.row, c.column) != Some(c.value) {
+            println!(
+                "   ⚠️  Boundary constraint violated: column {} at row {} should be {}",
+                c.column, c.row, c.value
+            );
+        }
     }
+}
 
-    println!(
-        "   ✅ Vanishing polynomial created (degree: {})",
-        result.degree()
-    );
-    result
+/// Evaluate the quotient Q(x) = C(x) / Z_T(x) at every point of the extended domain,
+/// with the closed-form Z_T of the transition domain. The extended domain does not
+/// meet the trace domain, so every division is defined, and they share one inversion.
+/// Q is low degree if and only if C vanishes on the transition domain.
+fn create_quotient_column<F: Field>(
+    constraint_values: &[F],
+    trace_length: usize,
+    extended_domain: &EvaluationDomain<F>,
+) -> Vec<F> {
+    println!("🔧 Creating quotient Q(x) = C(x) / Z_T(x) over the extended domain...");
+
+    let mut vanishing_values =
+        evaluate_transition_vanishing(&trace_domain(trace_length), &extended_domain.points);
+    batch_inverse(&mut vanishing_values)
+        .unwrap_or_else(|e| panic!("Z_T vanishes on the extended domain: {}", e));
+    let quotient: Vec<F> = constraint_values
+        .iter()
+        .zip(vanishing_values)
+        .map(|(&c, vanishing_inverse)| c.multiply(vanishing_inverse))
+        .collect();
+
+    println!("   ✅ Quotient evaluated at {} points", quotient.len());
+    quotient
 }
 
-/// Compute quotient polynomial Q(x) = C(x) / Z_H(x)
-/// This should be a low-degree polynomial if constraints are satisfied
-fn create_quotient_polynomial(
-    constraint_poly: &Polynomial,
-    vanishing_poly: &Polynomial,
-) -> Polynomial {
-    println!("🔧 Creating quotient polynomial Q(x) = C(x) / Z_H(x)...");
-
-    // If constraint polynomial is zero, quotient is zero
-    if constraint_poly.degree() == 0
-        && constraint_poly.coefficients.len() > 0
-        && constraint_poly.coefficients[0].is_zero()
-    {
-        println!("   ✅ Constraint polynomial is zero, quotient is zero");
-        return Polynomial::new(vec![0]);
+/// Evaluate one quotient per boundary constraint, B_k(x) = (P_c(x) - v) / (x - x_r),
+/// at every point of the extended domain, where P_c(x) is the LDE of column c.
+/// B_k is low degree if and only if column c at row r really equals v.
+fn create_boundary_quotient_columns<A: Air>(
+    air: &A,
+    extended_trace: &[Vec<A::Field>],
+    extended_domain: &EvaluationDomain<A::Field>,
+) -> Vec<Vec<A::Field>> {
+    println!("🔧 Creating boundary quotients...");
+
+    let trace_domain = trace_domain::<A::Field>(air.trace_length());
+    let mut quotients = Vec::new();
+
+    for c in air.boundary_constraints() {
+        // 1 / (x - x_r), one inversion for the whole domain
+        let row_point = trace_domain.element(c.row);
+        let mut denominators: Vec<A::Field> = extended_domain
+            .points
+            .iter()
+            .map(|x| x.subtract(row_point))
+            .collect();
+        batch_inverse(&mut denominators)
+            .unwrap_or_else(|e| panic!("x - x_r vanishes on the extended domain: {}", e));
+
+        let quotient = extended_trace[c.column]
+            .iter()
+            .zip(denominators)
+            .map(|(&p, denominator_inverse)| p.subtract(c.value).multiply(denominator_inverse))
+            .collect();
+        quotients.push(quotient);
     }
 
-    // If constraint polynomial has lower degree than vanishing polynomial,
-    // the quotient is zero and remainder is the constraint polynomial
-    if constraint_poly.degree() < vanishing_poly.degree() {
-        println!("   ✅ Constraint polynomial has lower degree than vanishing polynomial, quotient is zero");
-        return Polynomial::new(vec![0]);
-    }
+    println!("   ✅ Created {} boundary quotients", quotients.len());
+    quotients
+}
 
-    // Perform polynomial division: C(x) = Q(x) * Z_H(x) + R(x)
-    let (quotient, remainder) = constraint_poly.div(vanishing_poly);
+/// Build the Merkle tree over row-leaf hashes of the extended trace
+fn build_row_tree<F: Field, H: Hasher>(extended_trace: &[Vec<F>]) -> MerkleTree<H> {
+    let extended_size = extended_trace[0].len();
+    let row_leaf_hashes: Vec<H::Digest> = (0..extended_size)
+        .map(|i| {
+            let row: Vec<F> = extended_trace.iter().map(|col| col[i]).collect();
+            H::hash_leaf(&row)
+        })
+        .collect();
 
-    // In a valid STARK, the remainder should be zero (or very small)
-    if remainder.degree() > 0
-        || (remainder.coefficients.len() > 0 && !remainder.coefficients[0].is_zero())
-    {
-        println!(
-            "   ⚠️  Non-zero remainder in quotient computation: {}",
-            remainder
-        );
-    } else {
-        println!("   ✅ Quotient polynomial created with zero remainder");
-    }
+    // Build Merkle tree on row leaf hashes (pad internally)
+    let mut tree = MerkleTree::new();
+    tree.build_from_hashes(&row_leaf_hashes);
+    tree
+}
 
-    println!(
-        "   ✅ Quotient polynomial created (degree: {})",
-        quotient.degree()
-    );
-    quotient
+/// Collect the rows of column-major `columns` at `indices`
+fn rows_at<F: Field>(columns: &[Vec<F>], indices: &[usize]) -> Vec<Vec<F>> {
+    indices
+        .iter()
+        .map(|&i| columns.iter().map(|col| col[i]).collect())
+        .collect()
 }
 
-/// Step 2: Prover with Low Degree Extension
-pub fn prove_fibonacci(trace: Trace, field: FiniteField) -> StarkProof {
+/// Generate a STARK proof that `trace` satisfies `air`, with the given options,
+/// committing and drawing challenges with the hash `H`.
+/// Panics if the options cannot be used for this trace.
+pub fn prove<A: Air, H: Hasher>(
+    air: &A,
+    trace: Trace<A::Field>,
+    options: &ProofOptions,
+) -> StarkProof<A::Field, H> {
     println!("🔍 Starting STARK proof generation...");
     println!(
         "   Trace size: {} rows × {} columns",
         trace.num_rows(),
         trace.num_columns()
     );
+    assert_eq!(
+        trace.num_rows(),
+        air.trace_length(),
+        "trace length mismatch"
+    );
+    assert_eq!(
+        trace.num_columns(),
+        air.trace_width(),
+        "trace width mismatch"
+    );
+    assert!(trace.num_rows() > 1, "trace needs at least one transition");
+    assert!(
+        trace.num_rows().is_power_of_two(),
+        "trace length must be a power of two"
+    );
+    if let Err(e) = options.validate_for::<A::Field>(trace.num_rows(), air.max_constraint_degree())
+    {
+        panic!("invalid proof options: {}", e);
+    }
+    let security = proof_security::<A::Field, H>(options, trace.num_rows());
+    let protocol = estimate_protocol_security::<A::Field>(options, trace.num_rows(), 1);
+    println!(
+        "   🛡️  Estimated security: {} bits conjectured, {} bits proven",
+        security.conjectured_bits, security.proven_bits
+    );
+    println!(
+        "      (protocol alone: {} / {} bits; hash collision resistance: {} bits)",
+        protocol.conjectured_bits,
+        protocol.proven_bits,
+        H::COLLISION_RESISTANCE
+    );
+    if let Err(e) = security.check(TARGET_SECURITY_BITS) {
+        println!("   ⚠️  {}", e);
+    }
+    check_transition_constraints(air, &trace);
+    check_boundary_constraints(air, &trace);
 
     // Step 1: Perform Low Degree Extension
-    let extension_factor = EXTENSION_FACTOR; // Extend trace by constant factor
-    let extended_trace = extend_trace(&trace, field, extension_factor);
+    let extended_trace = extend_trace(&trace, options.blowup_factor, false).evaluations;
+    prove_extended_trace(air, extended_trace, options)
+}
 
-    // Step 2: Commit to the EXTENDED trace (row-leaf hashing)
-    // Build leaves per row by hashing all column values together
+/// Steps 2 onwards of `prove`: commit to `extended_trace`, the trace evaluated over
+/// the extended domain, and prove it satisfies `air`. Nothing here checks that the
+/// columns are low degree; that is left to the verifier, as with a cheating prover.
+fn prove_extended_trace<A: Air, H: Hasher>(
+    air: &A,
+    extended_trace: Vec<Vec<A::Field>>,
+    options: &ProofOptions,
+) -> StarkProof<A::Field, H> {
+    let trace_length = air.trace_length();
+    let blowup_factor = options.blowup_factor;
     let extended_size = extended_trace[0].len();
-    let num_cols = extended_trace.len();
-    let mut row_leaf_hashes: Vec<i128> = Vec::with_capacity(extended_size);
-    for i in 0..extended_size {
-        let mut acc: i128 = 0;
-        for c in 0..num_cols {
-            let h = extended_trace[c][i].hash();
-            acc = crate::merkle_tree::hash_two_inputs(acc, h);
-        }
-        // Use the accumulated hash directly as the leaf hash
-        row_leaf_hashes.push(acc);
-    }
+    let extended_domain = extended_domain(trace_length, blowup_factor);
 
-    // Build Merkle tree on row leaf hashes (pad internally)
-    let mut tree = MerkleTree::new();
-    tree.build_from_hashes(&row_leaf_hashes);
+    // Step 2: Commit to the EXTENDED trace (row-leaf hashing)
+    let tree = build_row_tree::<_, H>(&extended_trace);
 
     let commitment = tree.root().unwrap();
-    println!("   ✅ Extended trace committed: {}", commitment);
-
-    // Create a composition polynomial over original domain from the original trace
-    let (composition_poly, eval_domain) = create_fibonacci_constraint_poly(&trace, field);
-
-    // Create vanishing polynomial and quotient polynomial
-    let vanishing_poly = create_vanishing_polynomial(&eval_domain);
-    let quotient_poly = create_quotient_polynomial(&composition_poly, &vanishing_poly);
-
-    // FRI: fold evaluations. Pad evaluations to Merkle leaf_count
-    let mut fri_layers: Vec<Vec<FiniteFieldElement>> = Vec::new();
-    let leaf_count = tree.leaf_count();
-    let mut eval_leaves: Vec<FiniteFieldElement> = Vec::new();
-    // Use a single combined evaluation per row: take, for simplicity, the last column F(n)
-    for i in 0..extended_size {
-        eval_leaves.push(extended_trace[num_cols - 1][i]);
-    }
-    if eval_leaves.len() < leaf_count {
-        eval_leaves.resize(leaf_count, FiniteFieldElement::ZERO);
-    }
-    fri_layers.push(eval_leaves.clone());
-
-    // Educational fixed betas (in practice via Fiat–Shamir)
-    // Derive FRI betas via Fiat–Shamir from the Merkle root
-    let fri_betas = derive_fri_betas_from_commitment(commitment, 2);
-    let mut cur = eval_leaves;
-    for &beta in &fri_betas {
-        cur = fold_once(&cur, beta);
-        fri_layers.push(cur.clone());
-        if cur.len() <= 1 {
-            break;
-        }
-    }
+    println!(
+        "   ✅ Extended trace committed: {}",
+        digest_hex::<H>(&commitment)
+    );
+
+    // One transcript for the whole protocol, starting from the public inputs.
+    // Random coefficients for combining the transition constraints
+    let public_inputs = air.public_inputs();
+    let mut transcript = protocol_transcript(options, &public_inputs);
+    let alphas = derive_constraint_coefficients(
+        &mut transcript,
+        commitment,
+        air.num_transition_constraints(),
+    );
+
+    // Step 3: Composition polynomial over the extended domain, divided pointwise
+    // by the vanishing polynomial of the transition domain (rows 0..n-2)
+    let constraint_values = evaluate_composition(air, &extended_trace, blowup_factor, &alphas);
+    let quotient = create_quotient_column(&constraint_values, trace_length, &extended_domain);
+    let boundary_quotients =
+        create_boundary_quotient_columns(air, &extended_trace, &extended_domain);
+
+    // Step 4: Commit to the quotients evaluated over the extended domain.
+    // Each leaf is the row [Q(x), B_1(x), ..., B_m(x)]
+    let mut quotient_columns = vec![quotient];
+    quotient_columns.extend(boundary_quotients);
+    let quotient_tree = build_row_tree::<_, H>(&quotient_columns);
+    let quotient_commitment = quotient_tree.root().unwrap();
+    println!(
+        "   ✅ Quotients committed: {}",
+        digest_hex::<H>(&quotient_commitment)
+    );
+
+    // Step 5: FRI on a random combination of the quotient and trace columns,
+    // evaluated over the extended domain, so every committed column is tested for
+    // low degree. Each layer root goes into the transcript before its folding
+    // challenge is drawn.
+    let fri_columns: Vec<&Vec<A::Field>> = quotient_columns
+        .iter()
+        .chain(extended_trace.iter())
+        .collect();
+    let fri_coefficients =
+        derive_quotient_coefficients(&mut transcript, quotient_commitment, fri_columns.len());
+    let fri_values: Vec<A::Field> = (0..extended_size)
+        .map(|i| {
+            let row: Vec<A::Field> = fri_columns.iter().map(|col| col[i]).collect();
+            combine_constraints(&row, &fri_coefficients)
+        })
+        .collect();
+    let fri_prover = FriProver::commit(
+        &fri_values,
+        &extended_domain,
+        options
+            .fri_final_layer_size()
+            .expect("options were validated"),
+        &mut transcript,
+    );
+    println!(
+        "   ✅ FRI committed {} layers",
+        fri_prover.layer_commitments().len()
+    );
+
+    // Step 6: Grind a proof-of-work nonce after the last commitment, then derive
+    // the sample points and open them
+    let pow_nonce = transcript.grind(options.grinding_bits);
+    println!(
+        "   ⛏️  Proof-of-work nonce {} ({} bits)",
+        pow_nonce, options.grinding_bits
+    );
+    let sample_points = derive_sample_points(&mut transcript, extended_size, options.num_queries);
+    let fri_proof = fri_prover.prove(&sample_points);
+    let next_points: Vec<usize> = sample_points
+        .iter()
+        .map(|&i| next_index(i, extended_size, blowup_factor))
+        .collect();
 
-    // Create empty sampling data (will be filled by verifier)
     let sampling_data = SamplingData {
-        sample_points: Vec::new(),
-        sample_values: Vec::new(),
-        constraint_values: Vec::new(),
-        merkle_proofs: Vec::new(),
+        sample_values: rows_at(&extended_trace, &sample_points),
+        next_values: rows_at(&extended_trace, &next_points),
+        quotient_values: rows_at(&quotient_columns, &sample_points),
+        merkle_proofs: generate_merkle_proofs(&tree, &sample_points),
+        next_merkle_proofs: generate_merkle_proofs(&tree, &next_points),
+        quotient_merkle_proofs: generate_merkle_proofs(&quotient_tree, &sample_points),
+        sample_points,
     };
 
     StarkProof {
         trace_commitment: commitment,
-        trace_size: trace.num_rows(),
-        field,
-        eval_domain,
+        quotient_commitment,
+        public_inputs,
+        options: *options,
         sampling_data,
-        fri_layers,
-        fri_betas,
-        composition_poly,
-        quotient_poly,
+        fri_proof,
+        pow_nonce,
     }
 }
 
+/// Generate a STARK proof for a Fibonacci trace
+pub fn prove_fibonacci<F: Field, H: Hasher>(
+    trace: Trace<F>,
+    options: &ProofOptions,
+) -> StarkProof<F, H> {
+    let air = FibonacciAir::from_trace(&trace);
+    prove(&air, trace, options)
+}
+
 /// Generate Merkle proofs for sample points
-pub fn generate_merkle_proofs(
-    extended_trace: &[Vec<FiniteFieldElement>],
+pub fn generate_merkle_proofs<H: Hasher>(
+    tree: &MerkleTree<H>,
     sample_points: &[usize],
-) -> Vec<Vec<i128>> {
+) -> Vec<MerkleProof<H::Digest>> {
     println!(
         "🌳 Prover generating Merkle proofs for {} sample points...",
         sample_points.len()
     );
 
-    // Build the same row-leaf Merkle tree as in prove_fibonacci
-    let extended_size = extended_trace[0].len();
-    let num_cols = extended_trace.len();
-    let mut row_leaf_hashes: Vec<i128> = Vec::with_capacity(extended_size);
-    for i in 0..extended_size {
-        let mut acc: i128 = 0;
-        for c in 0..num_cols {
-            let h = extended_trace[c][i].hash();
-            acc = crate::merkle_tree::hash_two_inputs(acc, h);
-        }
-        row_leaf_hashes.push(acc);
-    }
-
-    let mut tree = MerkleTree::new();
-    tree.build_from_hashes(&row_leaf_hashes);
-
     // Generate proofs for each sample point
     let mut merkle_proofs = Vec::new();
     for &sample_point in sample_points {
@@ -315,124 +424,540 @@ pub fn generate_merkle_proofs(
                 "   ❌ Failed to generate Merkle proof for sample point {}",
                 sample_point
             );
-            merkle_proofs.push(Vec::new()); // Empty proof as fallback
+            // Empty path as fallback; it will not verify
+            merkle_proofs.push(MerkleProof {
+                index: sample_point,
+                siblings: Vec::new(),
+            });
         }
     }
 
     println!("   ✅ Generated {} Merkle proofs", merkle_proofs.len());
+    if let Some(multi) = tree.get_multi_proof(sample_points) {
+        let separate: usize = merkle_proofs.iter().map(|p| p.siblings.len()).sum();
+        println!(
+            "   📦 {} sibling hashes as separate paths, {} as one multi-proof",
+            separate,
+            multi.siblings.len()
+        );
+    }
     merkle_proofs
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::constants::DEFAULT_FIELD_SIZE;
+    use crate::air::BoundaryConstraint;
+    use crate::constants::DEFAULT_BLOWUP_FACTOR;
+    use crate::fri::derive_betas;
+    use crate::goldilocks::Goldilocks;
+    use crate::hashing::{Blake2s, Sha256, ToyHasher};
+    use crate::polynomial::ntt::coset_intt;
     use crate::trace::fibonacci;
-    use crate::verifier::verify_proof;
+    use crate::verifier::{quotient_degree_bound, transition_domain};
+    use crate::verifier::{verify, verify_with_security};
+
+    type F = FiniteFieldElement;
+
+    fn fibonacci_trace(num_steps: usize) -> Trace {
+        fibonacci::generate_fibonacci_trace(num_steps, F::ONE, F::ONE)
+    }
+
+    fn fibonacci_air(num_steps: usize, result: i128) -> FibonacciAir {
+        FibonacciAir::new(num_steps, F::ONE, F::new(result))
+    }
 
     #[test]
     fn test_fibonacci_prover() {
         // Generate a small Fibonacci trace
-        let trace = fibonacci::generate_fibonacci_trace(5, 1, 1);
-        let field = FiniteField::new(DEFAULT_FIELD_SIZE);
+        let trace = fibonacci_trace(8);
 
         // Generate proof
-        let mut proof = prove_fibonacci(trace.clone(), field);
-
-        // Set up sampling data like in main
-        let extension_factor = EXTENSION_FACTOR;
-        let extended_trace = super::extend_trace(&trace, proof.field, extension_factor);
-        let extended_trace_size = proof.trace_size * extension_factor;
-
-        let sample_points = crate::verifier::generate_sample_points(extended_trace_size, 5);
-        // Generate Merkle proofs by rebuilding the same tree (for testing only)
-        let merkle_proofs = super::generate_merkle_proofs(&extended_trace, &sample_points);
-
-        // Collect sample values (constraint values will be derived by verifier)
-        let mut sample_values = Vec::new();
-        for &sample_point in &sample_points {
-            let mut values_at_point = Vec::new();
-            for col in 0..extended_trace.len() {
-                values_at_point.push(extended_trace[col][sample_point]);
-            }
-            sample_values.push(values_at_point);
-        }
-
-        proof.sampling_data.sample_points = sample_points;
-        proof.sampling_data.sample_values = sample_values;
-        proof.sampling_data.constraint_values = Vec::new(); // Verifier will derive these
-        proof.sampling_data.merkle_proofs = merkle_proofs;
+        let proof: StarkProof = prove_fibonacci(trace, &ProofOptions::default());
 
         // Verify proof using verifier
-        let is_valid = verify_proof(&proof);
+        let air = fibonacci_air(8, 21);
+        let is_valid = verify(&air, &proof);
 
         assert!(is_valid, "Fibonacci proof should be valid");
     }
 
     #[test]
-    fn test_vanishing_polynomial() {
-        let field = FiniteField::new(DEFAULT_FIELD_SIZE);
-        let domain = EvaluationDomain::new_linear(field, 3); // points: 0, 1, 2
-
-        let vanishing_poly = create_vanishing_polynomial(&domain);
-
-        // Vanishing polynomial should be zero at all domain points
-        for i in 0..domain.size() {
-            let point = domain.element(i);
-            let value = vanishing_poly.evaluate(point);
-            assert_eq!(
-                value.value, 0,
-                "Vanishing polynomial should be zero at domain point {}",
-                i
-            );
+    fn test_invalid_trace_rejected() {
+        let mut trace = fibonacci_trace(8);
+        trace.trace[3][2] = trace.trace[3][2].add(F::ONE);
+        let air = fibonacci_air(8, 21);
+
+        let proof: StarkProof = prove(&air, trace, &ProofOptions::default());
+
+        assert!(!verify(&air, &proof), "Broken trace should not verify");
+    }
+
+    #[test]
+    fn test_wrong_air_rejected() {
+        let trace = fibonacci_trace(8);
+
+        let proof: StarkProof = prove_fibonacci(trace, &ProofOptions::default());
+
+        // The verifier expects a longer computation
+        let air = fibonacci_air(16, 987);
+        assert!(!verify(&air, &proof));
+    }
+
+    #[test]
+    fn test_wrong_result_rejected() {
+        let trace = fibonacci_trace(8);
+
+        // Prover claims F(7) = 22 instead of 21
+        let air = fibonacci_air(8, 22);
+        let proof: StarkProof = prove(&air, trace, &ProofOptions::default());
+
+        assert!(!verify(&air, &proof), "False claim should not verify");
+    }
+
+    #[test]
+    fn test_tampered_public_inputs_rejected() {
+        let trace = fibonacci_trace(8);
+
+        let mut proof: StarkProof = prove_fibonacci(trace, &ProofOptions::default());
+        proof.public_inputs.boundary_constraints[3].value = F::new(22);
+
+        // Neither the original nor the tampered claim accepts the altered proof
+        assert!(!verify(&fibonacci_air(8, 21), &proof));
+        assert!(!verify(&fibonacci_air(8, 22), &proof));
+    }
+
+    #[test]
+    fn test_every_commitment_drives_later_challenges() {
+        let trace = fibonacci_trace(8);
+        let air = fibonacci_air(8, 21);
+        let proof: StarkProof = prove(&air, trace, &ProofOptions::default());
+        let pi = &proof.public_inputs;
+
+        // Replays the verifier's challenge derivation with one input changed
+        let challenges = |trace_root: i128, quotient_root: i128, trace_length: usize| {
+            let mut pi = pi.clone();
+            pi.trace_length = trace_length;
+            let mut t = protocol_transcript(&proof.options, &pi);
+            let alphas = derive_constraint_coefficients::<F, ToyHasher>(&mut t, trace_root, 3);
+            let coefficients =
+                derive_quotient_coefficients::<F, ToyHasher>(&mut t, quotient_root, 8);
+            let betas = crate::fri::derive_betas(&proof.fri_proof, &mut t);
+            t.verify_pow(proof.pow_nonce, proof.options.grinding_bits);
+            let points = derive_sample_points(&mut t, 32, proof.options.num_queries);
+            (alphas, coefficients, betas, points)
+        };
+
+        let honest = challenges(proof.trace_commitment, proof.quotient_commitment, 8);
+        assert_eq!(honest.3, proof.sampling_data.sample_points);
+
+        let other_trace = challenges(proof.trace_commitment + 1, proof.quotient_commitment, 8);
+        assert_ne!(honest.0, other_trace.0);
+        assert_ne!(honest.1, other_trace.1);
+        assert_ne!(honest.2, other_trace.2);
+
+        let other_quotient = challenges(proof.trace_commitment, proof.quotient_commitment + 1, 8);
+        assert_eq!(honest.0, other_quotient.0);
+        assert_ne!(honest.1, other_quotient.1);
+        assert_ne!(honest.2, other_quotient.2);
+
+        let other_inputs = challenges(proof.trace_commitment, proof.quotient_commitment, 16);
+        assert_ne!(honest.0, other_inputs.0);
+        assert_ne!(honest.2, other_inputs.2);
+    }
+
+    #[test]
+    fn test_tampered_pow_nonce_rejected() {
+        let trace = fibonacci_trace(8);
+        let air = fibonacci_air(8, 21);
+
+        let mut proof: StarkProof = prove(&air, trace, &ProofOptions::default());
+        let bits = proof.options.grinding_bits;
+        assert!(bits > 0);
+        assert!(verify(&air, &proof), "Honest nonce should verify");
+
+        // Replay the verifier's transcript up to the proof-of-work check
+        let mut transcript = protocol_transcript(&proof.options, &proof.public_inputs);
+        let _: Vec<F> = derive_constraint_coefficients(
+            &mut transcript,
+            proof.trace_commitment,
+            air.num_transition_constraints(),
+        );
+        let _: Vec<F> = derive_quotient_coefficients(
+            &mut transcript,
+            proof.quotient_commitment,
+            1 + proof.public_inputs.boundary_constraints.len() + air.trace_width(),
+        );
+        derive_betas(&proof.fri_proof, &mut transcript);
+        assert!(transcript.clone().verify_pow(proof.pow_nonce, bits));
+
+        let bad_nonce = (0..u64::MAX)
+            .find(|&nonce| !transcript.clone().verify_pow(nonce, bits))
+            .unwrap();
+        proof.pow_nonce = bad_nonce;
+        assert!(
+            !verify(&air, &proof),
+            "Nonce without enough leading zero bits should not verify"
+        );
+    }
+
+    #[test]
+    fn test_proof_in_a_small_field() {
+        // 193 = 3 * 2^6 + 1 has a subgroup of order 64, enough for 8 rows and blowup 4
+        type F193 = FiniteFieldElement<193>;
+        let trace = fibonacci::generate_fibonacci_trace(8, F193::ONE, F193::ONE);
+        let air = FibonacciAir::from_trace(&trace);
+        assert_eq!(air.result, F193::from_u64(21));
+
+        let proof: StarkProof<F193> = prove(&air, trace, &ProofOptions::default());
+        assert!(verify(&air, &proof));
+        assert!(!verify(
+            &FibonacciAir::new(8, F193::ONE, F193::from_u64(22)),
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_proof_over_goldilocks() {
+        let trace = fibonacci::generate_fibonacci_trace(16, Goldilocks::ONE, Goldilocks::ONE);
+        let air = FibonacciAir::from_trace(&trace);
+        let options = ProofOptions {
+            num_queries: 32,
+            grinding_bits: 4,
+            ..ProofOptions::default()
+        };
+
+        let proof: StarkProof<Goldilocks> = prove(&air, trace, &options);
+        assert!(verify(&air, &proof));
+        // 32 · 2 + 4 query bits, capped just below 64 - 6 by the field; the ToyHasher
+        // commitments still give no security
+        let security = proof_security::<Goldilocks, ToyHasher>(&proof.options, 16);
+        let protocol = estimate_protocol_security::<Goldilocks>(&proof.options, 16, 1);
+        assert_eq!(security.conjectured_bits, 0);
+        assert_eq!(protocol.conjectured_bits, 56);
+
+        let mut tampered = proof.clone();
+        let opened = &mut tampered.sampling_data.quotient_values[0][0];
+        *opened = opened.add(Goldilocks::ONE);
+        assert!(!verify(&air, &tampered));
+    }
+
+    #[test]
+    fn test_proof_with_real_hashes() {
+        fn prove_and_verify<H: Hasher>() -> StarkProof<F, H> {
+            let trace = fibonacci_trace(8);
+            let air = fibonacci_air(8, 21);
+            let proof: StarkProof<F, H> = prove(&air, trace, &ProofOptions::default());
+            assert!(verify(&air, &proof));
+
+            let mut tampered = proof.clone();
+            tampered.quotient_commitment = H::hash_bytes(b"another root");
+            assert!(!verify(&air, &tampered));
+            proof
         }
 
-        // Vanishing polynomial should be non-zero outside the domain
-        let outside_point = FiniteFieldElement::new_fielded(3, field);
-        let outside_value = vanishing_poly.evaluate(outside_point);
+        let sha = prove_and_verify::<Sha256>();
+        let blake = prove_and_verify::<Blake2s>();
+        // Same statement and options, but other commitments and so other queries
+        assert_ne!(sha.trace_commitment, blake.trace_commitment);
         assert_ne!(
-            outside_value.value, 0,
-            "Vanishing polynomial should be non-zero outside domain"
+            sha.sampling_data.sample_points,
+            blake.sampling_data.sample_points
         );
     }
 
     #[test]
-    fn test_quotient_polynomial() {
-        let field = FiniteField::new(DEFAULT_FIELD_SIZE);
-        let domain = EvaluationDomain::new_linear(field, 3); // points: 0, 1, 2
+    fn test_non_default_options() {
+        let trace = fibonacci_trace(16);
+        let air = FibonacciAir::from_trace(&trace);
+        let options = ProofOptions {
+            blowup_factor: 8,
+            num_queries: 12,
+            fri_max_remainder_degree: 1,
+            grinding_bits: 4,
+            ..ProofOptions::default()
+        };
+
+        let proof: StarkProof = prove(&air, trace, &options);
+        assert_eq!(proof.options, options);
+        assert_eq!(proof.sampling_data.sample_points.len(), 12);
+        assert_eq!(proof.fri_proof.final_layer.len(), 16);
+        assert!(verify(&air, &proof));
+
+        // The verifier follows the options in the proof; changing them breaks it
+        let mut altered = proof.clone();
+        altered.options.num_queries = 11;
+        assert!(!verify(&air, &altered));
+        let mut altered = proof.clone();
+        altered.options.fri_max_remainder_degree = 0;
+        assert!(!verify(&air, &altered));
+        let mut altered = proof;
+        altered.options.fri_folding_factor = 4;
+        assert!(!verify(&air, &altered));
+    }
+
+    #[test]
+    fn test_oversized_options_rejected() {
+        let trace = fibonacci_trace(8);
+        let air = fibonacci_air(8, 21);
+        let proof: StarkProof = prove(&air, trace, &ProofOptions::default());
+
+        // Sizes derived from these overflow usize; the verifier must refuse, not panic
+        let mut huge_blowup = proof.clone();
+        huge_blowup.options.blowup_factor = 1 << 62;
+        let mut huge_remainder = proof;
+        huge_remainder.options.fri_max_remainder_degree = usize::MAX;
+        for altered in [huge_blowup, huge_remainder] {
+            assert!(!verify(&air, &altered));
+            assert!(!verify_with_security(&air, &altered, 0));
+        }
+    }
 
-        // Create a simple constraint polynomial that's zero at domain points
-        // C(x) = x(x-1)(x-2) = x^3 - 3x^2 + 2x
-        let constraint_poly = Polynomial::new(vec![0, 2, -3, 1]);
+    #[test]
+    #[should_panic(expected = "invalid proof options")]
+    fn test_unusable_options_panic() {
+        let trace = fibonacci_trace(8);
+        let options = ProofOptions {
+            num_queries: 0,
+            ..ProofOptions::default()
+        };
+        prove_fibonacci::<_, ToyHasher>(trace, &options);
+    }
 
-        let vanishing_poly = create_vanishing_polynomial(&domain);
-        let quotient_poly = create_quotient_polynomial(&constraint_poly, &vanishing_poly);
+    #[test]
+    fn test_security_target_depends_on_the_hasher() {
+        let trace = fibonacci_trace(8);
+        let air = fibonacci_air(8, 21);
+        let proof: StarkProof = prove(&air, trace, &ProofOptions::default());
 
-        // The quotient should be a constant (degree 0) since C(x) = (x-0)(x-1)(x-2) * 1
+        // ToyHasher commitments have no collision resistance, so any target refuses
         assert_eq!(
-            quotient_poly.degree(),
-            0,
-            "Quotient polynomial should be constant"
+            proof_security::<F, ToyHasher>(&proof.options, 8).conjectured_bits,
+            0
         );
+        assert!(verify_with_security(&air, &proof, 0));
+        assert!(!verify_with_security(&air, &proof, 1));
+
+        // Goldilocks with SHA-256 commitments: the protocol's 56 bits are kept
+        let trace = fibonacci::generate_fibonacci_trace(16, Goldilocks::ONE, Goldilocks::ONE);
+        let air = FibonacciAir::from_trace(&trace);
+        let options = ProofOptions {
+            num_queries: 32,
+            grinding_bits: 4,
+            ..ProofOptions::default()
+        };
+        let proof: StarkProof<Goldilocks, Sha256> = prove(&air, trace, &options);
+        let security = proof_security::<Goldilocks, Sha256>(&proof.options, 16);
+        assert_eq!(security.conjectured_bits, 56);
+        assert!(verify_with_security(&air, &proof, 50));
+        assert!(verify_with_security(&air, &proof, 56));
+        assert!(!verify_with_security(&air, &proof, 57));
+    }
+
+    #[test]
+    fn test_tampered_quotient_rejected() {
+        let trace = fibonacci_trace(8);
+        let air = fibonacci_air(8, 21);
+
+        let mut proof: StarkProof = prove(&air, trace, &ProofOptions::default());
+        let opened = &mut proof.sampling_data.quotient_values[0][0];
+        *opened = opened.add(F::ONE);
+
+        assert!(
+            !verify(&air, &proof),
+            "Altered quotient opening should not verify"
+        );
+    }
+
+    #[test]
+    fn test_tampered_fri_rejected() {
+        let trace = fibonacci_trace(8);
+        let air = fibonacci_air(8, 21);
+
+        let mut proof: StarkProof = prove(&air, trace, &ProofOptions::default());
+        let value = &mut proof.fri_proof.queries[0].layers[1].values[0];
+        *value = value.add(F::ONE);
+
+        assert!(
+            !verify(&air, &proof),
+            "Altered FRI opening should not verify"
+        );
+    }
+
+    #[test]
+    fn test_extend_trace_matches_column_polynomials() {
+        let trace = fibonacci_trace(8);
+
+        let lde = extend_trace(&trace, DEFAULT_BLOWUP_FACTOR, true);
+        let column_polys = lde.coefficients.unwrap();
+        assert_eq!(lde.evaluations.len(), 3);
+        assert_eq!(column_polys.len(), 3);
+
+        let trace_domain = trace_domain::<F>(8);
+        let extended_domain = extended_domain::<F>(8, DEFAULT_BLOWUP_FACTOR);
+        for (col, poly) in column_polys.iter().enumerate() {
+            // Interpolates the trace column...
+            for (row, &value) in trace.get_column(col).iter().enumerate() {
+                assert_eq!(poly.evaluate(trace_domain.element(row)), value);
+            }
+            // ...and the LDE is its evaluation over the extended domain
+            assert_eq!(lde.evaluations[col].len(), 32);
+            for (i, &value) in lde.evaluations[col].iter().enumerate() {
+                assert_eq!(poly.evaluate(extended_domain.element(i)), value);
+            }
+        }
+
+        assert!(extend_trace(&trace, DEFAULT_BLOWUP_FACTOR, false)
+            .coefficients
+            .is_none());
+    }
+
+    #[test]
+    fn test_extend_large_trace() {
+        let num_rows = 1 << 16;
+        let trace = Trace::from_computation(num_rows, 2, |step, _| {
+            vec![F::new(step as i128), F::new((step * step) as i128)]
+        });
+
+        let lde = extend_trace(&trace, DEFAULT_BLOWUP_FACTOR, true);
+        assert_eq!(lde.evaluations[1].len(), num_rows * DEFAULT_BLOWUP_FACTOR);
+
+        // The extension has the degree of the column polynomial
+        let offset = F::multiplicative_generator();
+        let coefficients = coset_intt(&lde.evaluations[1], offset).unwrap();
+        assert!(coefficients[num_rows..].iter().all(|c| c.is_zero()));
         assert_eq!(
-            quotient_poly.coefficients[0].value, 1,
-            "Quotient should be 1"
+            coefficients[..num_rows],
+            lde.coefficients.unwrap()[1].coefficients[..]
         );
+    }
 
-        // Verify: C(x) = Q(x) * Z_H(x) at a few test points
-        let test_points = vec![0, 1, 2, 3, 4];
-        for &i in &test_points {
-            let point = FiniteFieldElement::new_fielded(i, field);
-            let c_value = constraint_poly.evaluate(point);
-            let z_value = vanishing_poly.evaluate(point);
-            let q_value = quotient_poly.evaluate(point);
-            let expected = q_value.multiply(z_value);
-
-            assert_eq!(
-                c_value.value, expected.value,
-                "C({}) = {} should equal Q({}) * Z_H({}) = {}",
-                i, c_value.value, i, i, expected.value
-            );
+    /// Degree of the polynomial taking `values` on the extended domain
+    fn degree_on_extended_domain(values: &[F]) -> usize {
+        let coefficients = coset_intt(values, F::multiplicative_generator()).unwrap();
+        coefficients.iter().rposition(|c| !c.is_zero()).unwrap_or(0)
+    }
+
+    #[test]
+    fn test_quotients_are_low_degree_only_for_a_valid_trace() {
+        let air = fibonacci_air(8, 21);
+        let domain = extended_domain::<F>(8, DEFAULT_BLOWUP_FACTOR);
+        let alphas = vec![F::new(3); air.num_transition_constraints()];
+        let quotients = |trace: &Trace| {
+            let lde = extend_trace(trace, DEFAULT_BLOWUP_FACTOR, false).evaluations;
+            let c = evaluate_composition(&air, &lde, DEFAULT_BLOWUP_FACTOR, &alphas);
+            let q = create_quotient_column(&c, 8, &domain);
+            let b = create_boundary_quotient_columns(&air, &lde, &domain);
+            (q, b)
+        };
+
+        // Q has degree at most (d - 1)(n - 1) and each B_k at most n - 2
+        let (q, b) = quotients(&fibonacci_trace(8));
+        assert!(degree_on_extended_domain(&q) <= quotient_degree_bound(&air));
+        assert!(b.iter().all(|b| degree_on_extended_domain(b) <= 6));
+
+        let mut broken = fibonacci_trace(8);
+        broken.trace[3][2] = broken.trace[3][2].add(F::ONE);
+        let (q, _) = quotients(&broken);
+        assert!(degree_on_extended_domain(&q) > quotient_degree_bound(&air));
+
+        let mut wrong_result = fibonacci_trace(8);
+        wrong_result.trace[7][2] = F::new(22);
+        let (_, b) = quotients(&wrong_result);
+        assert!(b.iter().any(|b| degree_on_extended_domain(b) > 6));
+    }
+
+    #[test]
+    fn test_batch_inverted_quotients_match_polynomial_division() {
+        let trace = fibonacci_trace(8);
+        let air = fibonacci_air(8, 21);
+        let domain = extended_domain::<F>(8, DEFAULT_BLOWUP_FACTOR);
+        let alphas = vec![F::new(3); air.num_transition_constraints()];
+        let lde = extend_trace(&trace, DEFAULT_BLOWUP_FACTOR, true);
+        let column_polys = lde.coefficients.unwrap();
+        let evaluate = |poly: &Polynomial<F>| -> Vec<F> {
+            domain.points.iter().map(|&x| poly.evaluate(x)).collect()
+        };
+
+        // Q = C / Z_T, with Z_T the product of (x - t) over the transition domain
+        let c = evaluate_composition(&air, &lde.evaluations, DEFAULT_BLOWUP_FACTOR, &alphas);
+        let c_poly =
+            Polynomial::new_ff(coset_intt(&c, F::multiplicative_generator()).unwrap()).trim();
+        let z_poly = transition_domain::<F>(8)
+            .points
+            .iter()
+            .fold(Polynomial::new_ff(vec![F::ONE]), |z, &t| {
+                z.multiply(&Polynomial::new_ff(vec![t.negate(), F::ONE]))
+            });
+        let (q_poly, remainder) = c_poly.div(&z_poly);
+        assert!(remainder.coefficients.iter().all(|r| r.is_zero()));
+        assert_eq!(create_quotient_column(&c, 8, &domain), evaluate(&q_poly));
+
+        // B_k = (P_c - v) / (x - x_r)
+        let boundary = create_boundary_quotient_columns(&air, &lde.evaluations, &domain);
+        for (c, b) in air.boundary_constraints().iter().zip(&boundary) {
+            let numerator = column_polys[c.column].sub(&Polynomial::new_ff(vec![c.value]));
+            let row_point = trace_domain::<F>(8).element(c.row);
+            let denominator = Polynomial::new_ff(vec![row_point.negate(), F::ONE]);
+            let (b_poly, remainder) = numerator.div(&denominator);
+            assert!(remainder.coefficients.iter().all(|r| r.is_zero()));
+            assert_eq!(*b, evaluate(&b_poly));
+        }
+    }
+
+    /// Counter in column 0 (x' = x + 1, starting at 0) next to a scratch column 1
+    /// that no transition or boundary constraint mentions
+    struct ScratchColumnAir;
+
+    impl Air for ScratchColumnAir {
+        type Field = F;
+
+        fn trace_width(&self) -> usize {
+            2
+        }
+
+        fn trace_length(&self) -> usize {
+            8
         }
+
+        fn transition_constraint_degrees(&self) -> Vec<usize> {
+            vec![1]
+        }
+
+        fn evaluate_transition(&self, current: &[F], next: &[F]) -> Vec<F> {
+            vec![next[0].subtract(current[0]).subtract(F::ONE)]
+        }
+
+        fn boundary_constraints(&self) -> Vec<BoundaryConstraint<F>> {
+            vec![BoundaryConstraint {
+                column: 0,
+                row: 0,
+                value: F::ZERO,
+            }]
+        }
+    }
+
+    #[test]
+    fn test_unconstrained_column_must_be_low_degree() {
+        let air = ScratchColumnAir;
+        let trace = Trace::from_computation(8, 2, |step, _| vec![F::new(step as i128), F::new(7)]);
+        let options = ProofOptions::default();
+
+        let proof: StarkProof = prove(&air, trace.clone(), &options);
+        assert!(verify(&air, &proof), "Honest proof should verify");
+
+        // A cheating prover commits to scratch values that no polynomial of degree
+        // below n takes on the extended domain. Only the low-degree test sees them.
+        let mut extended_trace = extend_trace(&trace, options.blowup_factor, false).evaluations;
+        for (i, value) in extended_trace[1].iter_mut().enumerate() {
+            *value = F::new(((i * i * i + 5 * i) % 997) as i128);
+        }
+        assert!(degree_on_extended_domain(&extended_trace[1]) >= 8);
+
+        let proof: StarkProof = prove_extended_trace(&air, extended_trace, &options);
+        assert!(
+            !verify(&air, &proof),
+            "Trace column that is not low degree should not verify"
+        );
     }
 }
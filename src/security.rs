@@ -0,0 +1,236 @@
+//! Soundness estimates for a choice of `ProofOptions`.
+//!
+//! The conjectured level follows the ethSTARK conjecture: every query costs a
+//! cheating prover log2(blowup) bits, grinding adds its bits, and the extension
+//! field must be large enough that random challenges avoid the bad points of the
+//! extended domain. The proven level uses the unique-decoding bound instead: a
+//! far-from-low-degree word passes each query with probability up to (1 + ρ) / 2,
+//! ρ = 1 / blowup, and every FRI round adds a field-sized error term. Both are
+//! capped by the collision resistance of the commitment hash.
+
+use core::fmt;
+
+use crate::field::Field;
+use crate::hashing::Hasher;
+use crate::proof_options::ProofOptions;
+
+/// Estimated soundness of a proof configuration, in bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecurityLevel {
+    /// Security under the ethSTARK conjecture (what most deployments quote)
+    pub conjectured_bits: u32,
+    /// Security provable in the unique-decoding regime
+    pub proven_bits: u32,
+}
+
+/// Why a configuration is refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecurityError {
+    /// The conjectured security is below the requested target
+    BelowTarget { bits: u32, target: u32 },
+}
+
+impl fmt::Display for SecurityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecurityError::BelowTarget { bits, target } => write!(
+                f,
+                "{} bits of conjectured security, below the {}-bit target",
+                bits, target
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SecurityError {}
+
+impl SecurityLevel {
+    /// Refuse the configuration if its conjectured security is below `target_bits`
+    pub fn check(&self, target_bits: u32) -> Result<(), SecurityError> {
+        if self.conjectured_bits < target_bits {
+            return Err(SecurityError::BelowTarget {
+                bits: self.conjectured_bits,
+                target: target_bits,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Estimate the soundness of proving a trace over `F` of `trace_length` rows
+/// with `options`, drawing challenges from the degree `extension_degree`
+/// extension of `F` (1 for the base field), assuming an ideal hash
+pub fn estimate_protocol_security<F: Field>(
+    options: &ProofOptions,
+    trace_length: usize,
+    extension_degree: usize,
+) -> SecurityLevel {
+    let field_bits = (F::CHARACTERISTIC as f64).log2() * extension_degree as f64;
+    // Sizes in bits, so options that were not validated cannot overflow
+    let blowup = options.blowup_factor as f64;
+    let lde_bits = (trace_length as f64).log2() + blowup.log2();
+    // Queries are distinct, so there are never more than points in the domain
+    let num_queries = match trace_length.checked_mul(options.blowup_factor) {
+        Some(lde_size) => options.num_queries.min(lde_size),
+        None => options.num_queries,
+    } as f64;
+    let grinding = options.grinding_bits as f64;
+
+    let conjectured_queries = num_queries * blowup.log2() + grinding;
+    let conjectured_field = field_bits - lde_bits;
+
+    let rate = 1.0 / blowup;
+    let proven_queries = num_queries * -((1.0 + rate) / 2.0).log2() + grinding;
+    // log2 of the final layer size, (d + 1).next_power_of_two() · blowup
+    let remainder_bits = (usize::BITS - options.fri_max_remainder_degree.leading_zeros()) as f64;
+    let fri_rounds = (lde_bits - remainder_bits - blowup.log2()).floor().max(0.0);
+    let proven_field = field_bits - lde_bits - (fri_rounds + 1.0).log2();
+
+    let bits = |queries: f64, field: f64| (queries.min(field) - 1.0).floor().max(0.0) as u32;
+    SecurityLevel {
+        conjectured_bits: bits(conjectured_queries, conjectured_field),
+        proven_bits: bits(proven_queries, proven_field),
+    }
+}
+
+/// `estimate_protocol_security`, capped by the collision resistance of the
+/// commitment hash `H`
+pub fn estimate_security<F: Field, H: Hasher>(
+    options: &ProofOptions,
+    trace_length: usize,
+    extension_degree: usize,
+) -> SecurityLevel {
+    let protocol = estimate_protocol_security::<F>(options, trace_length, extension_degree);
+    SecurityLevel {
+        conjectured_bits: protocol.conjectured_bits.min(H::COLLISION_RESISTANCE),
+        proven_bits: protocol.proven_bits.min(H::COLLISION_RESISTANCE),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::finite_field::FiniteFieldElement;
+    use crate::goldilocks::Goldilocks;
+    use crate::hashing::{Poseidon, Sha256, ToyHasher};
+
+    type F = FiniteFieldElement;
+
+    #[test]
+    fn default_options() {
+        // 5 queries · 2 bits + 8 grinding bits; the field term is 31.6 - 5 bits
+        let level = estimate_security::<F, Sha256>(&ProofOptions::default(), 8, 1);
+        assert_eq!(level.conjectured_bits, 17);
+        // 5 · -log2(5/8) + 8 = 11.4
+        assert_eq!(level.proven_bits, 10);
+        assert!(level.proven_bits <= level.conjectured_bits);
+    }
+
+    #[test]
+    fn queries_grinding_and_blowup_add_bits() {
+        let base = ProofOptions {
+            grinding_bits: 0,
+            ..ProofOptions::default()
+        };
+        let bits = |options: ProofOptions| estimate_security::<F, Sha256>(&options, 8, 1);
+
+        assert_eq!(bits(base).conjectured_bits, 9);
+        let more_queries = bits(ProofOptions {
+            num_queries: 10,
+            ..base
+        });
+        assert_eq!(more_queries.conjectured_bits, 19);
+        let grinding = bits(ProofOptions {
+            grinding_bits: 4,
+            ..base
+        });
+        assert_eq!(grinding.conjectured_bits, 13);
+        let blowup = bits(ProofOptions {
+            blowup_factor: 16,
+            ..base
+        });
+        assert_eq!(blowup.conjectured_bits, 19);
+        assert!(blowup.proven_bits > bits(base).proven_bits);
+    }
+
+    #[test]
+    fn field_and_extension_cap_the_query_bits() {
+        let options = ProofOptions {
+            blowup_factor: 8,
+            num_queries: 40,
+            grinding_bits: 16,
+            ..ProofOptions::default()
+        };
+        // 136 query bits, but only 31.6 - 13 bits from the field
+        assert_eq!(
+            estimate_security::<F, Sha256>(&options, 1 << 10, 1).conjectured_bits,
+            17
+        );
+        // A quadratic extension: 63.2 - 13
+        assert_eq!(
+            estimate_security::<F, Sha256>(&options, 1 << 10, 2).conjectured_bits,
+            49
+        );
+        // A larger field: p is just below 2^64, so just below 128 - 13 bits
+        assert_eq!(
+            estimate_security::<Goldilocks, Sha256>(&options, 1 << 10, 2).conjectured_bits,
+            113
+        );
+        // The same bound through a small field type: 2 · 6.6 bits cannot cover
+        // a domain of 2^13 points
+        assert_eq!(
+            estimate_security::<FiniteFieldElement<97>, Sha256>(&options, 1 << 10, 2)
+                .conjectured_bits,
+            0
+        );
+    }
+
+    #[test]
+    fn hash_caps_everything() {
+        let options = ProofOptions {
+            blowup_factor: 16,
+            num_queries: 60,
+            grinding_bits: 20,
+            ..ProofOptions::default()
+        };
+        let ideal = estimate_protocol_security::<Goldilocks>(&options, 1 << 10, 3);
+        let sha = estimate_security::<Goldilocks, Sha256>(&options, 1 << 10, 3);
+        assert_eq!(sha.conjectured_bits, 128);
+        let poseidon = estimate_security::<Goldilocks, Poseidon>(&options, 1 << 10, 3);
+        assert_eq!(poseidon.conjectured_bits, 62);
+        assert_eq!(poseidon.proven_bits, 62);
+        let toy = estimate_security::<Goldilocks, ToyHasher>(&options, 1 << 10, 3);
+        assert_eq!(toy.conjectured_bits, 0);
+        assert_eq!(toy.proven_bits, 0);
+        assert_eq!(ideal.conjectured_bits, 176);
+        assert_eq!(ideal.proven_bits, 73);
+    }
+
+    #[test]
+    fn unvalidated_options_do_not_overflow() {
+        // Neither the domain size nor the final layer size fits in usize
+        let options = ProofOptions {
+            blowup_factor: 1 << 62,
+            fri_max_remainder_degree: usize::MAX,
+            ..ProofOptions::default()
+        };
+        let level = estimate_protocol_security::<Goldilocks>(&options, 1 << 10, 1);
+        assert!(level.proven_bits <= level.conjectured_bits);
+    }
+
+    #[test]
+    fn check_refuses_below_target() {
+        let level = SecurityLevel {
+            conjectured_bits: 80,
+            proven_bits: 40,
+        };
+        assert_eq!(level.check(80), Ok(()));
+        assert_eq!(
+            level.check(100),
+            Err(SecurityError::BelowTarget {
+                bits: 80,
+                target: 100
+            })
+        );
+    }
+}
@@ -0,0 +1,585 @@
+use core::fmt;
+
+use crate::air::{BoundaryConstraint, PublicInputs};
+use crate::field::Field;
+use crate::fri::{FriDecommitment, FriProof, FriQuery};
+use crate::hashing::Hasher;
+use crate::merkle_tree::MerkleProof;
+use crate::proof_options::ProofOptions;
+use crate::verifier::{SamplingData, StarkProof};
+
+/// First bytes of every serialized proof
+pub const PROOF_MAGIC: [u8; 4] = *b"STRK";
+/// Version of the binary format written by `StarkProof::to_bytes`
+pub const PROOF_FORMAT_VERSION: u16 = 5;
+
+/// Why a byte string is not a valid serialized proof
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeserializationError {
+    /// The input does not start with `PROOF_MAGIC`
+    BadMagic,
+    /// The format version is not one this code can read
+    UnsupportedVersion(u16),
+    /// The input ends before a value that should be there
+    UnexpectedEnd { needed: usize, remaining: usize },
+    /// A length prefix is larger than the rest of the input can hold
+    LengthTooLarge(u64),
+    /// The proof is over a field with another characteristic than the one it is
+    /// read into
+    FieldMismatch { expected: u64, found: u64 },
+    /// A field element is not in its canonical encoding (e.g. not reduced)
+    NonCanonicalElement,
+    /// Bytes that are not a digest of the proof's hash
+    InvalidDigest,
+    /// Bytes left over after the proof
+    TrailingBytes(usize),
+}
+
+impl fmt::Display for DeserializationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeserializationError::BadMagic => write!(f, "not a serialized proof (bad magic)"),
+            DeserializationError::UnsupportedVersion(v) => {
+                write!(f, "unsupported proof format version {}", v)
+            }
+            DeserializationError::UnexpectedEnd { needed, remaining } => write!(
+                f,
+                "input truncated: needed {} bytes, {} left",
+                needed, remaining
+            ),
+            DeserializationError::LengthTooLarge(len) => {
+                write!(f, "length prefix {} exceeds the input", len)
+            }
+            DeserializationError::FieldMismatch { expected, found } => write!(
+                f,
+                "proof is over the field of characteristic {}, expected {}",
+                found, expected
+            ),
+            DeserializationError::NonCanonicalElement => {
+                write!(f, "field element is not canonically encoded")
+            }
+            DeserializationError::InvalidDigest => write!(f, "invalid hash digest"),
+            DeserializationError::TrailingBytes(n) => {
+                write!(f, "{} trailing bytes after the proof", n)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DeserializationError {}
+
+/// Appends little-endian values to a buffer
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn u16(&mut self, v: u16) {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn u32(&mut self, v: u32) {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn u64(&mut self, v: u64) {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn usize(&mut self, v: usize) {
+        self.u64(v as u64);
+    }
+
+    /// Digests are written as `H::digest_to_bytes`, `H::DIGEST_SIZE` bytes each
+    fn digest<H: Hasher>(&mut self, d: &H::Digest) {
+        self.bytes.extend_from_slice(&H::digest_to_bytes(d));
+    }
+
+    /// Field elements are written in their canonical encoding, which is also what
+    /// Merkle leaves hash
+    fn element<F: Field>(&mut self, e: &F) {
+        self.bytes.extend_from_slice(&e.to_canonical_bytes());
+    }
+
+    fn elements<F: Field>(&mut self, values: &[F]) {
+        self.usize(values.len());
+        for v in values {
+            self.element(v);
+        }
+    }
+
+    fn rows<F: Field>(&mut self, rows: &[Vec<F>]) {
+        self.usize(rows.len());
+        for row in rows {
+            self.elements(row);
+        }
+    }
+
+    fn hashes<H: Hasher>(&mut self, hashes: &[H::Digest]) {
+        self.usize(hashes.len());
+        for h in hashes {
+            self.digest::<H>(h);
+        }
+    }
+
+    fn path<H: Hasher>(&mut self, path: &MerkleProof<H::Digest>) {
+        self.usize(path.index);
+        self.hashes::<H>(&path.siblings);
+    }
+
+    fn paths<H: Hasher>(&mut self, paths: &[MerkleProof<H::Digest>]) {
+        self.usize(paths.len());
+        for path in paths {
+            self.path::<H>(path);
+        }
+    }
+}
+
+/// Reads little-endian values, checking bounds before every read
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], DeserializationError> {
+        if self.bytes.len() < n {
+            return Err(DeserializationError::UnexpectedEnd {
+                needed: n,
+                remaining: self.bytes.len(),
+            });
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u16(&mut self) -> Result<u16, DeserializationError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, DeserializationError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, DeserializationError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn usize(&mut self) -> Result<usize, DeserializationError> {
+        let v = self.u64()?;
+        usize::try_from(v).map_err(|_| DeserializationError::LengthTooLarge(v))
+    }
+
+    fn digest<H: Hasher>(&mut self) -> Result<H::Digest, DeserializationError> {
+        H::digest_from_bytes(self.take(H::DIGEST_SIZE)?).ok_or(DeserializationError::InvalidDigest)
+    }
+
+    /// Read a length prefix for items of at least `min_item_size` bytes each,
+    /// rejecting lengths the remaining input cannot hold before allocating
+    fn len(&mut self, min_item_size: usize) -> Result<usize, DeserializationError> {
+        let len = self.u64()?;
+        if len > (self.bytes.len() / min_item_size) as u64 {
+            return Err(DeserializationError::LengthTooLarge(len));
+        }
+        Ok(len as usize)
+    }
+
+    fn element<F: Field>(&mut self) -> Result<F, DeserializationError> {
+        F::from_canonical_bytes(self.take(F::ENCODED_SIZE)?)
+            .ok_or(DeserializationError::NonCanonicalElement)
+    }
+
+    fn elements<F: Field>(&mut self) -> Result<Vec<F>, DeserializationError> {
+        let len = self.len(F::ENCODED_SIZE)?;
+        (0..len).map(|_| self.element()).collect()
+    }
+
+    fn rows<F: Field>(&mut self) -> Result<Vec<Vec<F>>, DeserializationError> {
+        let len = self.len(8)?;
+        (0..len).map(|_| self.elements()).collect()
+    }
+
+    fn hashes<H: Hasher>(&mut self) -> Result<Vec<H::Digest>, DeserializationError> {
+        let len = self.len(H::DIGEST_SIZE.max(1))?;
+        (0..len).map(|_| self.digest::<H>()).collect()
+    }
+
+    fn path<H: Hasher>(&mut self) -> Result<MerkleProof<H::Digest>, DeserializationError> {
+        Ok(MerkleProof {
+            index: self.usize()?,
+            siblings: self.hashes::<H>()?,
+        })
+    }
+
+    fn paths<H: Hasher>(&mut self) -> Result<Vec<MerkleProof<H::Digest>>, DeserializationError> {
+        let len = self.len(16)?;
+        (0..len).map(|_| self.path::<H>()).collect()
+    }
+}
+
+impl<F: Field, H: Hasher> StarkProof<F, H> {
+    /// Serialize the proof: a header (magic, format version, field characteristic,
+    /// proof options) followed by every other field in declaration order. Integers
+    /// are little-endian, field elements use their canonical encoding, digests
+    /// `H::digest_to_bytes`, every vector has a u64 length prefix.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut w = Writer { bytes: Vec::new() };
+        w.bytes.extend_from_slice(&PROOF_MAGIC);
+        w.u16(PROOF_FORMAT_VERSION);
+        let options = &self.options;
+        w.u64(F::CHARACTERISTIC);
+        w.usize(options.blowup_factor);
+        w.usize(options.num_queries);
+        w.usize(options.fri_folding_factor);
+        w.usize(options.fri_max_remainder_degree);
+        w.u32(options.grinding_bits);
+
+        w.digest::<H>(&self.trace_commitment);
+        w.digest::<H>(&self.quotient_commitment);
+
+        w.usize(self.public_inputs.trace_length);
+        w.usize(self.public_inputs.boundary_constraints.len());
+        for c in &self.public_inputs.boundary_constraints {
+            w.usize(c.column);
+            w.usize(c.row);
+            w.element(&c.value);
+        }
+
+        let s = &self.sampling_data;
+        w.usize(s.sample_points.len());
+        for &p in &s.sample_points {
+            w.usize(p);
+        }
+        w.rows(&s.sample_values);
+        w.rows(&s.next_values);
+        w.rows(&s.quotient_values);
+        w.paths::<H>(&s.merkle_proofs);
+        w.paths::<H>(&s.next_merkle_proofs);
+        w.paths::<H>(&s.quotient_merkle_proofs);
+
+        let fri = &self.fri_proof;
+        w.hashes::<H>(&fri.layer_commitments);
+        w.elements(&fri.final_layer);
+        w.usize(fri.queries.len());
+        for query in &fri.queries {
+            w.usize(query.index);
+            w.usize(query.layers.len());
+            for opening in &query.layers {
+                w.element(&opening.values[0]);
+                w.element(&opening.values[1]);
+                w.path::<H>(&opening.merkle_proofs[0]);
+                w.path::<H>(&opening.merkle_proofs[1]);
+            }
+        }
+
+        w.u64(self.pow_nonce);
+
+        w.bytes
+    }
+
+    /// Parse a proof over F with `H` digests written by `to_bytes`. Every read is
+    /// bounds-checked; truncated or malformed input, or a proof over another field,
+    /// gives an error, never a panic.
+    pub fn from_bytes(bytes: &[u8]) -> Result<StarkProof<F, H>, DeserializationError> {
+        let mut r = Reader { bytes };
+        if r.take(PROOF_MAGIC.len())? != PROOF_MAGIC {
+            return Err(DeserializationError::BadMagic);
+        }
+        let version = r.u16()?;
+        if version != PROOF_FORMAT_VERSION {
+            return Err(DeserializationError::UnsupportedVersion(version));
+        }
+        let characteristic = r.u64()?;
+        if characteristic != F::CHARACTERISTIC {
+            return Err(DeserializationError::FieldMismatch {
+                expected: F::CHARACTERISTIC,
+                found: characteristic,
+            });
+        }
+        let options = ProofOptions {
+            blowup_factor: r.usize()?,
+            num_queries: r.usize()?,
+            fri_folding_factor: r.usize()?,
+            fri_max_remainder_degree: r.usize()?,
+            grinding_bits: r.u32()?,
+        };
+
+        let trace_commitment = r.digest::<H>()?;
+        let quotient_commitment = r.digest::<H>()?;
+
+        let trace_length = r.usize()?;
+        let num_constraints = r.len(16 + F::ENCODED_SIZE)?;
+        let mut boundary_constraints = Vec::with_capacity(num_constraints);
+        for _ in 0..num_constraints {
+            boundary_constraints.push(BoundaryConstraint {
+                column: r.usize()?,
+                row: r.usize()?,
+                value: r.element()?,
+            });
+        }
+
+        let num_points = r.len(8)?;
+        let sample_points = (0..num_points)
+            .map(|_| r.usize())
+            .collect::<Result<Vec<_>, _>>()?;
+        let sampling_data = SamplingData {
+            sample_points,
+            sample_values: r.rows()?,
+            next_values: r.rows()?,
+            quotient_values: r.rows()?,
+            merkle_proofs: r.paths::<H>()?,
+            next_merkle_proofs: r.paths::<H>()?,
+            quotient_merkle_proofs: r.paths::<H>()?,
+        };
+
+        let layer_commitments = r.hashes::<H>()?;
+        let final_layer = r.elements()?;
+        let num_queries = r.len(16)?;
+        let mut queries = Vec::with_capacity(num_queries);
+        for _ in 0..num_queries {
+            let index = r.usize()?;
+            let num_layers = r.len(2 * F::ENCODED_SIZE + 32)?;
+            let mut layers = Vec::with_capacity(num_layers);
+            for _ in 0..num_layers {
+                let values = [r.element()?, r.element()?];
+                let merkle_proofs = [r.path::<H>()?, r.path::<H>()?];
+                layers.push(FriDecommitment {
+                    values,
+                    merkle_proofs,
+                });
+            }
+            queries.push(FriQuery { index, layers });
+        }
+
+        let pow_nonce = r.u64()?;
+
+        if !r.bytes.is_empty() {
+            return Err(DeserializationError::TrailingBytes(r.bytes.len()));
+        }
+
+        Ok(StarkProof {
+            trace_commitment,
+            quotient_commitment,
+            public_inputs: PublicInputs {
+                trace_length,
+                boundary_constraints,
+            },
+            options,
+            sampling_data,
+            fri_proof: FriProof {
+                layer_commitments,
+                final_layer,
+                queries,
+            },
+            pow_nonce,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::air::fibonacci::FibonacciAir;
+    use crate::constants::DEFAULT_FIELD_SIZE;
+    use crate::finite_field::FiniteFieldElement;
+    use crate::goldilocks::{Goldilocks, MODULUS};
+    use crate::hashing::{Poseidon, Sha256};
+    use crate::prover::prove;
+    use crate::trace::fibonacci;
+    use crate::verifier::verify;
+
+    type F = FiniteFieldElement;
+
+    fn sample_proof() -> (FibonacciAir, StarkProof) {
+        let trace = fibonacci::generate_fibonacci_trace(8, F::ONE, F::ONE);
+        let air = FibonacciAir::from_trace(&trace);
+        let proof = prove(&air, trace, &ProofOptions::default());
+        (air, proof)
+    }
+
+    #[test]
+    fn round_trip_keeps_every_field() {
+        let (air, proof) = sample_proof();
+        let bytes = proof.to_bytes();
+        assert_eq!(&bytes[..4], b"STRK");
+
+        let decoded = StarkProof::<F>::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+
+        assert_eq!(decoded.options, proof.options);
+        assert_eq!(decoded.trace_commitment, proof.trace_commitment);
+        assert_eq!(decoded.quotient_commitment, proof.quotient_commitment);
+        assert_eq!(decoded.public_inputs, proof.public_inputs);
+        assert_eq!(decoded.pow_nonce, proof.pow_nonce);
+
+        let (s, t) = (&decoded.sampling_data, &proof.sampling_data);
+        assert_eq!(s.sample_points, t.sample_points);
+        assert_eq!(s.sample_values, t.sample_values);
+        assert_eq!(s.next_values, t.next_values);
+        assert_eq!(s.quotient_values, t.quotient_values);
+        assert_eq!(s.merkle_proofs, t.merkle_proofs);
+        assert_eq!(s.next_merkle_proofs, t.next_merkle_proofs);
+        assert_eq!(s.quotient_merkle_proofs, t.quotient_merkle_proofs);
+
+        let (f, g) = (&decoded.fri_proof, &proof.fri_proof);
+        assert_eq!(f.layer_commitments, g.layer_commitments);
+        assert_eq!(f.final_layer, g.final_layer);
+        assert_eq!(f.queries.len(), g.queries.len());
+        for (a, b) in f.queries.iter().zip(g.queries.iter()) {
+            assert_eq!(a.index, b.index);
+            for (x, y) in a.layers.iter().zip(b.layers.iter()) {
+                assert_eq!(x.values, y.values);
+                assert_eq!(x.merkle_proofs, y.merkle_proofs);
+            }
+        }
+
+        assert!(verify(&air, &decoded));
+    }
+
+    #[test]
+    fn altered_options_decode_but_do_not_verify() {
+        let (air, proof) = sample_proof();
+        let bytes = proof.to_bytes();
+
+        // Blowup factor right after the field characteristic: 4 -> 8 and 4 -> 3
+        let offset = 4 + 2 + 8;
+        for blowup in [8u64, 3] {
+            let mut bad = bytes.clone();
+            bad[offset..offset + 8].copy_from_slice(&blowup.to_le_bytes());
+            let decoded = StarkProof::<F>::from_bytes(&bad).unwrap();
+            assert_eq!(decoded.options.blowup_factor, blowup as usize);
+            assert!(!verify(&air, &decoded));
+        }
+    }
+
+    #[test]
+    fn truncated_input_is_rejected() {
+        let (_, proof) = sample_proof();
+        let bytes = proof.to_bytes();
+        for len in 0..bytes.len() {
+            assert!(
+                StarkProof::<F>::from_bytes(&bytes[..len]).is_err(),
+                "prefix of {} bytes should not parse",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        let (_, proof) = sample_proof();
+        let bytes = proof.to_bytes();
+
+        let mut bad = bytes.clone();
+        bad[0] = b'X';
+        assert_eq!(
+            StarkProof::<F>::from_bytes(&bad).err(),
+            Some(DeserializationError::BadMagic)
+        );
+
+        let mut bad = bytes.clone();
+        bad[4] = 7;
+        assert_eq!(
+            StarkProof::<F>::from_bytes(&bad).err(),
+            Some(DeserializationError::UnsupportedVersion(7))
+        );
+
+        let mut bad = bytes.clone();
+        bad.push(0);
+        assert_eq!(
+            StarkProof::<F>::from_bytes(&bad).err(),
+            Some(DeserializationError::TrailingBytes(1))
+        );
+
+        // Number of boundary constraints (after magic, version, options, two roots
+        // and the trace length) claims far more data than there is
+        let offset = 4 + 2 + 8 + 4 * 8 + 4 + 32 + 8;
+        let mut bad = bytes.clone();
+        bad[offset..offset + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(
+            StarkProof::<F>::from_bytes(&bad).err(),
+            Some(DeserializationError::LengthTooLarge(u64::MAX))
+        );
+
+        // A field element that is not reduced modulo the prime
+        let mut unreduced = proof.clone();
+        unreduced.fri_proof.final_layer[0].value = DEFAULT_FIELD_SIZE;
+        assert_eq!(
+            StarkProof::<F>::from_bytes(&unreduced.to_bytes()).err(),
+            Some(DeserializationError::NonCanonicalElement)
+        );
+    }
+
+    #[test]
+    fn goldilocks_proof_round_trip() {
+        let trace = fibonacci::generate_fibonacci_trace(8, Goldilocks::ONE, Goldilocks::ONE);
+        let air = FibonacciAir::from_trace(&trace);
+        let proof: StarkProof<Goldilocks> = prove(&air, trace, &ProofOptions::default());
+
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes[6..14], MODULUS.to_le_bytes());
+        let decoded = StarkProof::<Goldilocks>::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert!(verify(&air, &decoded));
+
+        assert_eq!(
+            StarkProof::<F>::from_bytes(&bytes).err(),
+            Some(DeserializationError::FieldMismatch {
+                expected: DEFAULT_FIELD_SIZE as u64,
+                found: MODULUS,
+            })
+        );
+    }
+
+    #[test]
+    fn sha256_proof_round_trip() {
+        let trace = fibonacci::generate_fibonacci_trace(8, F::ONE, F::ONE);
+        let air = FibonacciAir::from_trace(&trace);
+        let proof: StarkProof<F, Sha256> = prove(&air, trace, &ProofOptions::default());
+
+        let bytes = proof.to_bytes();
+        let decoded = StarkProof::<F, Sha256>::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.trace_commitment, proof.trace_commitment);
+        assert_eq!(
+            decoded.fri_proof.layer_commitments,
+            proof.fri_proof.layer_commitments
+        );
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert!(verify(&air, &decoded));
+
+        // Commitments right after the options, 32 bytes each instead of 16
+        let commitments = 4 + 2 + 8 + 4 * 8 + 4;
+        assert_eq!(bytes[commitments..commitments + 32], proof.trace_commitment);
+        assert!(bytes.len() > sample_proof().1.to_bytes().len());
+    }
+
+    #[test]
+    fn non_canonical_digest_is_rejected() {
+        let trace = fibonacci::generate_fibonacci_trace(8, F::ONE, F::ONE);
+        let air = FibonacciAir::from_trace(&trace);
+        let proof: StarkProof<F, Poseidon> = prove(&air, trace, &ProofOptions::default());
+        let mut bytes = proof.to_bytes();
+        assert!(StarkProof::<F, Poseidon>::from_bytes(&bytes).is_ok());
+
+        // First element of the trace commitment set to p
+        let commitments = 4 + 2 + 8 + 4 * 8 + 4;
+        bytes[commitments..commitments + 4]
+            .copy_from_slice(&(DEFAULT_FIELD_SIZE as u32).to_le_bytes());
+        assert_eq!(
+            StarkProof::<F, Poseidon>::from_bytes(&bytes).err(),
+            Some(DeserializationError::InvalidDigest)
+        );
+    }
+
+    #[test]
+    fn proof_over_another_field_is_rejected() {
+        let (_, proof) = sample_proof();
+        assert_eq!(
+            StarkProof::<FiniteFieldElement<97>>::from_bytes(&proof.to_bytes()).err(),
+            Some(DeserializationError::FieldMismatch {
+                expected: 97,
+                found: DEFAULT_FIELD_SIZE as u64,
+            })
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::field::Field;
 use crate::finite_field::FiniteFieldElement;
 use core::panic;
 
@@ -5,14 +6,14 @@ use core::panic;
 /// First dimension is rows (time steps), second is columns (state variables).
 /// All rows must have the same number of columns.
 #[derive(Clone)]
-pub struct Trace {
-    pub trace: Vec<Vec<i128>>,
+pub struct Trace<F: Field = FiniteFieldElement> {
+    pub trace: Vec<Vec<F>>,
     pub num_columns: usize,
 }
 
-impl Trace {
+impl<F: Field> Trace<F> {
     /// Create a new trace with validation
-    pub fn new(trace: Vec<Vec<i128>>) -> Self {
+    pub fn new(trace: Vec<Vec<F>>) -> Self {
         if trace.is_empty() {
             panic!("Trace cannot be empty");
         }
@@ -48,45 +49,30 @@ impl Trace {
     }
 
     /// Get value at specific row and column
-    pub fn get(&self, row: usize, col: usize) -> Option<i128> {
+    pub fn get(&self, row: usize, col: usize) -> Option<F> {
         self.trace.get(row)?.get(col).copied()
     }
 
     /// Get entire row
-    pub fn get_row(&self, row: usize) -> Option<&Vec<i128>> {
+    pub fn get_row(&self, row: usize) -> Option<&Vec<F>> {
         self.trace.get(row)
     }
 
     /// Get entire column
-    pub fn get_column(&self, col: usize) -> Vec<i128> {
+    pub fn get_column(&self, col: usize) -> Vec<F> {
         self.trace.iter().map(|row| row[col]).collect()
     }
 
-    /// Convert to finite field elements
-    pub fn to_finite_field_elements(
-        &self,
-        field: crate::finite_field::FiniteField,
-    ) -> Vec<Vec<FiniteFieldElement>> {
-        self.trace
-            .iter()
-            .map(|row| {
-                row.iter()
-                    .map(|&val| FiniteFieldElement::new_fielded(val, field))
-                    .collect()
-            })
-            .collect()
-    }
-
     /// Create trace from a computation function
-    pub fn from_computation<F>(num_steps: usize, num_vars: usize, mut compute: F) -> Self
+    pub fn from_computation<C>(num_steps: usize, num_vars: usize, mut compute: C) -> Self
     where
-        F: FnMut(usize, &[i128]) -> Vec<i128>,
+        C: FnMut(usize, &[F]) -> Vec<F>,
     {
-        let mut trace: Vec<Vec<i128>> = Vec::with_capacity(num_steps);
+        let mut trace: Vec<Vec<F>> = Vec::with_capacity(num_steps);
 
         for step in 0..num_steps {
             let prev_state = if step == 0 {
-                vec![0; num_vars] // Initial state
+                vec![F::ZERO; num_vars] // Initial state
             } else {
                 trace[step - 1].clone()
             };
@@ -110,22 +96,20 @@ impl Trace {
 /// Example: Fibonacci trace generator
 pub mod fibonacci {
     use super::Trace;
+    use crate::field::Field;
 
     /// Generate a Fibonacci trace: F(n) = F(n-1) + F(n-2)
     /// Columns: [F(n-2), F(n-1), F(n)]
-    pub fn generate_fibonacci_trace(num_steps: usize, a: i128, _b: i128) -> Trace {
+    pub fn generate_fibonacci_trace<F: Field>(num_steps: usize, a: F, _b: F) -> Trace<F> {
         Trace::from_computation(num_steps, 3, move |step, prev_state| {
             if step == 0 {
-                // Initial state: [0, a, a] - F(0) = a, F(-1) = 0
-                vec![0, a, a]
-            } else if step == 1 {
-                // F(1) = a, F(0) = a, F(-1) = 0
-                vec![a, a, a]
+                // Initial state: F(0) = a, F(-1) = 0, F(-2) = F(0) - F(-1) = a
+                vec![a, F::ZERO, a]
             } else {
                 // F(n-2), F(n-1), F(n) = F(n-1) + F(n-2)
                 let f_n_minus_2 = prev_state[1]; // Previous F(n-1) becomes F(n-2)
                 let f_n_minus_1 = prev_state[2]; // Previous F(n) becomes F(n-1)
-                let f_n = f_n_minus_1 + f_n_minus_2;
+                let f_n = f_n_minus_1.add(f_n_minus_2);
                 vec![f_n_minus_2, f_n_minus_1, f_n]
             }
         })
@@ -136,27 +120,42 @@ pub mod fibonacci {
 mod tests {
     use super::*;
 
+    /// Trace over the default field from integer rows
+    fn trace_of(rows: Vec<Vec<i128>>) -> Trace {
+        Trace::new(
+            rows.into_iter()
+                .map(|row| row.into_iter().map(FiniteFieldElement::new).collect())
+                .collect(),
+        )
+    }
+
     #[test]
     fn test_trace_creation() {
         let trace_data = vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]];
-        let trace = Trace::new(trace_data);
+        let trace = trace_of(trace_data);
 
         assert_eq!(trace.num_rows(), 3);
         assert_eq!(trace.num_columns(), 3);
-        assert_eq!(trace.get(0, 0), Some(1));
-        assert_eq!(trace.get(1, 2), Some(6));
+        assert_eq!(trace.get(0, 0), Some(FiniteFieldElement::new(1)));
+        assert_eq!(trace.get(1, 2), Some(FiniteFieldElement::new(6)));
     }
 
     #[test]
     fn test_fibonacci_trace() {
-        let trace = fibonacci::generate_fibonacci_trace(5, 1, 1);
+        let one = FiniteFieldElement::new(1);
+        let trace = fibonacci::generate_fibonacci_trace(5, one, one);
+        let column: Vec<i128> = trace.get_column(2).iter().map(|v| v.value).collect();
 
         // First few Fibonacci numbers: 1, 1, 2, 3, 5
-        assert_eq!(trace.get(0, 2), Some(1)); // F(0) = 1
-        assert_eq!(trace.get(1, 2), Some(1)); // F(1) = 1
-        assert_eq!(trace.get(2, 2), Some(2)); // F(2) = 1+1 = 2
-        assert_eq!(trace.get(3, 2), Some(3)); // F(3) = 2+1 = 3
-        assert_eq!(trace.get(4, 2), Some(5)); // F(4) = 3+2 = 5
+        assert_eq!(column, [1, 1, 2, 3, 5]);
+    }
+
+    #[test]
+    fn test_fibonacci_trace_wraps_in_small_field() {
+        // 1, 1, 2, 3, 5, 8, 13, 21 mod 17
+        type F17 = FiniteFieldElement<17>;
+        let trace = fibonacci::generate_fibonacci_trace(8, F17::ONE, F17::ONE);
+        assert_eq!(trace.get(7, 2), Some(F17::from_i128(4)));
     }
 
     #[test]
@@ -166,6 +165,6 @@ mod tests {
             vec![1, 2, 3],
             vec![4, 5], // Different number of columns!
         ];
-        Trace::new(trace_data);
+        trace_of(trace_data);
     }
 }
@@ -1,124 +1,136 @@
-use crate::constants::{DEFAULT_FIELD_SIZE, EXTENSION_FACTOR};
+use crate::air::{combine_constraints, Air, PublicInputs};
 use crate::evaluation_domain::EvaluationDomain;
+use crate::fiat_shamir::Transcript;
+use crate::field::{batch_inverse_skipping_zeros, Field};
 use crate::finite_field::FiniteFieldElement;
-use crate::polynomial::polynomial::Polynomial;
-use crate::{fiat_shamir::Transcript, finite_field::FiniteField};
+use crate::fri::{derive_betas, verify_fri, FriProof};
+use crate::hashing::{digest_hex, Hasher, ToyHasher};
+use crate::merkle_tree::{MerkleProof, MerkleTree};
+use crate::proof_options::ProofOptions;
+use crate::security::{estimate_security, SecurityLevel};
 
 /// Random sampling data for verification
-pub struct SamplingData {
-    /// Random points chosen by verifier
+#[derive(Debug, Clone)]
+pub struct SamplingData<F: Field = FiniteFieldElement, H: Hasher = ToyHasher> {
+    /// Random points derived via Fiat–Shamir from the commitments
     pub sample_points: Vec<usize>,
-    /// Values at those points (provided by prover)
-    pub sample_values: Vec<Vec<FiniteFieldElement>>,
-    /// Constraint polynomial values at sample points
-    pub constraint_values: Vec<FiniteFieldElement>,
-    /// Merkle proofs for the sample points
-    pub merkle_proofs: Vec<Vec<i128>>,
+    /// Extended trace rows at the sample points
+    pub sample_values: Vec<Vec<F>>,
+    /// Extended trace rows at the next points (see `next_index`)
+    pub next_values: Vec<Vec<F>>,
+    /// Quotient rows [Q(x), B_1(x), ..., B_m(x)] at the sample points
+    pub quotient_values: Vec<Vec<F>>,
+    /// Merkle proofs for `sample_values` against the trace commitment
+    pub merkle_proofs: Vec<MerkleProof<H::Digest>>,
+    /// Merkle proofs for `next_values` against the trace commitment
+    pub next_merkle_proofs: Vec<MerkleProof<H::Digest>>,
+    /// Merkle proofs for `quotient_values` against the quotient commitment
+    pub quotient_merkle_proofs: Vec<MerkleProof<H::Digest>>,
 }
 
-/// STARK proof structure (shared between prover and verifier)
-pub struct StarkProof {
+/// STARK proof structure (shared between prover and verifier), with every
+/// commitment and the transcript over the hash `H`
+#[derive(Debug, Clone)]
+pub struct StarkProof<F: Field = FiniteFieldElement, H: Hasher = ToyHasher> {
     /// Merkle root of the extended trace
-    pub trace_commitment: i128,
-    /// Original trace size (number of rows)
-    pub trace_size: usize,
-    /// The field used
-    pub field: crate::finite_field::FiniteField,
-    /// Evaluation domain for the extended trace
-    pub eval_domain: EvaluationDomain,
+    pub trace_commitment: H::Digest,
+    /// Merkle root of the quotients over the extended domain
+    pub quotient_commitment: H::Digest,
+    /// What is being proven: trace length and claimed boundary values
+    pub public_inputs: PublicInputs<F>,
+    /// Parameters the proof was generated with
+    pub options: ProofOptions,
     /// Random sampling points and values
-    pub sampling_data: SamplingData,
-    /// FRI folding layers over the same leaves as Merkle (padded to power-of-two)
-    pub fri_layers: Vec<Vec<FiniteFieldElement>>,
-    /// Folding betas used per round (educational, fixed for now)
-    pub fri_betas: Vec<FiniteFieldElement>,
-    /// Composition polynomial C(x) = f(x+2) - f(x+1) - f(x) over original domain
-    pub composition_poly: Polynomial,
-    /// Quotient polynomial Q(x) = C(x) / Z_H(x) - should be low degree
-    pub quotient_poly: Polynomial,
+    pub sampling_data: SamplingData<F, H>,
+    /// FRI proof that the combined quotient and trace columns are low degree,
+    /// queried at the sample points
+    pub fri_proof: FriProof<F, H>,
+    /// Proof-of-work nonce ground after the last commitment, before the queries
+    pub pow_nonce: u64,
 }
 
-/// Verify constraints using composition polynomial provided by prover
-fn verify_fibonacci_constraints(
-    sample_points: &[usize],
-    trace_size: usize,
-    field: FiniteField,
-    composition_poly: &Polynomial,
-) -> bool {
-    println!("🔧 Verifying Fibonacci constraints using composition polynomial...");
+/// Trace domain: the subgroup of order n, row r sits at g^r
+pub fn trace_domain<F: Field>(trace_length: usize) -> EvaluationDomain<F> {
+    EvaluationDomain::new_subgroup(trace_length)
+}
 
-    let mut valid = true;
-    let mut checked_count = 0;
+/// Extended domain: the coset h·<ω> of the subgroup of order n · blowup_factor,
+/// with h the field generator. Since ω^blowup_factor = g, it is closed under
+/// x -> g · x, and it does not meet the trace domain, so quotients are defined everywhere.
+pub fn extended_domain<F: Field>(trace_length: usize, blowup_factor: usize) -> EvaluationDomain<F> {
+    EvaluationDomain::new_coset(trace_length * blowup_factor, F::multiplicative_generator())
+}
 
-    // Check composition polynomial at all sampled points
-    for (i, &sample_point) in sample_points.iter().enumerate() {
-        // Evaluate composition polynomial at this point
-        let extended_eval_domain =
-            EvaluationDomain::new_linear(field, trace_size * EXTENSION_FACTOR);
-        let point = extended_eval_domain.element(sample_point);
-        let constraint_value = composition_poly.evaluate(point);
+/// Transition domain: every trace row except the last one, where the
+/// transition constraints must hold
+pub fn transition_domain<F: Field>(trace_length: usize) -> EvaluationDomain<F> {
+    let mut points = trace_domain(trace_length).points;
+    points.pop();
+    EvaluationDomain::from_points(points)
+}
 
-        if !constraint_value.is_zero() {
-            println!(
-                "   ❌ Sample {} (point {}): C({}) = {} (should be 0)",
-                i, sample_point, sample_point, constraint_value.value
-            );
-            valid = false;
-        } else {
-            println!(
-                "   ✅ Sample {} (point {}): C({}) = 0",
-                i, sample_point, sample_point
-            );
-        }
-        checked_count += 1;
-    }
+/// Z_T(x) = (x^n - 1) / (x - g^(n-1)), the vanishing polynomial of the transition
+/// domain, at each point outside the trace domain. The divisions share one inversion.
+pub fn evaluate_transition_vanishing<F: Field>(
+    trace_domain: &EvaluationDomain<F>,
+    points: &[F],
+) -> Vec<F> {
+    let last_row = trace_domain.element(trace_domain.size() - 1);
+    let mut denominators: Vec<F> = points.iter().map(|x| x.subtract(last_row)).collect();
+    batch_inverse_skipping_zeros(&mut denominators);
+    points
+        .iter()
+        .zip(denominators)
+        .map(|(&x, inverse)| trace_domain.evaluate_vanishing(x).multiply(inverse))
+        .collect()
+}
 
-    if valid {
-        println!(
-            "   ✅ All Fibonacci constraints verified! (checked {} points)",
-            checked_count
-        );
-    } else {
-        println!("   ❌ Some Fibonacci constraints failed verification!");
-    }
+/// Index of g · x in the extended domain, given the index of x: the next trace row
+pub fn next_index(index: usize, extended_size: usize, blowup_factor: usize) -> usize {
+    (index + blowup_factor) % extended_size
+}
 
-    valid
+/// Degree bound for the quotients: Q has degree at most (d - 1)(n - 1) for constraints
+/// of degree d, and each boundary quotient has degree at most n - 2
+pub fn quotient_degree_bound<A: Air>(air: &A) -> usize {
+    air.trace_length() * air.max_constraint_degree().saturating_sub(1).max(1)
 }
 
-/// Verify quotient polynomial at sample points
-/// This checks that Q(x) = C(x) / Z_H(x) is well-defined and low-degree
-fn verify_quotient_polynomial(
-    sample_points: &[usize],
-    trace_size: usize,
-    field: FiniteField,
-    composition_poly: &Polynomial,
-    quotient_poly: &Polynomial,
+/// Verify transition constraints at sample points.
+/// The verifier recomputes C(x) = Σ_j α_j · C_j(row(x), row(g · x)) from the opened
+/// trace rows and checks it against the opened quotient: C(x) = Q(x) · Z_H(x)
+fn verify_transition_constraints<A: Air, H: Hasher>(
+    air: &A,
+    proof: &StarkProof<A::Field, H>,
+    alphas: &[A::Field],
 ) -> bool {
-    println!("🔧 Verifying quotient polynomial Q(x) = C(x) / Z_H(x)...");
+    println!("🔧 Verifying transition constraints against the quotient...");
 
+    let trace_size = proof.public_inputs.trace_length;
+    let sampling_data = &proof.sampling_data;
     let mut valid = true;
-    let mut checked_count = 0;
-
-    // Create the original domain for vanishing polynomial
-    let original_domain = EvaluationDomain::new_linear(field, trace_size);
 
-    // Check quotient polynomial at all sampled points
-    for (i, &sample_point) in sample_points.iter().enumerate() {
-        let extended_eval_domain =
-            EvaluationDomain::new_linear(field, trace_size * EXTENSION_FACTOR);
-        let point = extended_eval_domain.element(sample_point);
-
-        // Evaluate composition polynomial C(x)
-        let constraint_value = composition_poly.evaluate(point);
-
-        // Evaluate vanishing polynomial Z_H(x) at the point
-        let vanishing_value = original_domain.evaluate_vanishing(point);
-
-        // Evaluate quotient polynomial Q(x)
-        let quotient_value = quotient_poly.evaluate(point);
+    // Transition constraints hold on rows 0..n-2, so Z_H vanishes there
+    let trace_domain = trace_domain(trace_size);
+    let extended_eval_domain = extended_domain(trace_size, proof.options.blowup_factor);
+    let points: Vec<A::Field> = sampling_data
+        .sample_points
+        .iter()
+        .map(|&index| extended_eval_domain.element(index))
+        .collect();
+    let vanishing_values = evaluate_transition_vanishing(&trace_domain, &points);
+
+    for (i, &sample_point) in sampling_data.sample_points.iter().enumerate() {
+        // C(x) from the opened rows
+        let residuals = air.evaluate_transition(
+            &sampling_data.sample_values[i],
+            &sampling_data.next_values[i],
+        );
+        let constraint_value = combine_constraints(&residuals, alphas);
 
-        // Check: C(x) should equal Q(x) * Z_H(x)
-        let expected_constraint = quotient_value.multiply(vanishing_value);
+        // Q(x) * Z_H(x) from the opened quotient
+        let quotient_value = sampling_data.quotient_values[i][0];
+        let expected_constraint = quotient_value.multiply(vanishing_values[i]);
 
         if constraint_value != expected_constraint {
             println!(
@@ -126,213 +138,426 @@ fn verify_quotient_polynomial(
                 i,
                 sample_point,
                 sample_point,
-                constraint_value.value,
+                constraint_value,
                 sample_point,
                 sample_point,
-                expected_constraint.value
+                expected_constraint
             );
             valid = false;
         } else {
             println!(
                 "   ✅ Sample {} (point {}): Q({}) * Z_H({}) = C({}) = {}",
-                i, sample_point, sample_point, sample_point, sample_point, constraint_value.value
+                i, sample_point, sample_point, sample_point, sample_point, constraint_value
             );
         }
-        checked_count += 1;
     }
 
     if valid {
         println!(
-            "   ✅ Quotient polynomial verification passed! (checked {} points)",
-            checked_count
+            "   ✅ All transition constraints verified! (checked {} points)",
+            sampling_data.sample_points.len()
         );
     } else {
-        println!("   ❌ Quotient polynomial verification failed!");
+        println!("   ❌ Some transition constraints failed verification!");
     }
 
     valid
 }
 
-/// Verify random sampling: check that constraint polynomial is zero at sample points
-pub fn verify_random_sampling(proof: &StarkProof) -> bool {
-    println!("🎲 Verifying random sampling...");
+/// Verify boundary quotients at sample points: P_c(x) - v = B_k(x) · (x - x_r),
+/// where P_c(x) and B_k(x) are the Merkle-verified opened values
+pub fn verify_boundary_constraints<F: Field, H: Hasher>(proof: &StarkProof<F, H>) -> bool {
+    println!("🔧 Verifying boundary constraints...");
 
-    // Verify constraints using composition polynomial
-    let constraint_valid = verify_fibonacci_constraints(
-        &proof.sampling_data.sample_points,
-        proof.trace_size,
-        proof.field,
-        &proof.composition_poly,
-    );
+    let constraints = &proof.public_inputs.boundary_constraints;
+    let trace_size = proof.public_inputs.trace_length;
+    let sampling_data = &proof.sampling_data;
 
-    // Verify quotient polynomial
-    let quotient_valid = verify_quotient_polynomial(
-        &proof.sampling_data.sample_points,
-        proof.trace_size,
-        proof.field,
-        &proof.composition_poly,
-        &proof.quotient_poly,
-    );
+    let trace_domain = trace_domain::<F>(trace_size);
+    let extended_eval_domain = extended_domain::<F>(trace_size, proof.options.blowup_factor);
+    let mut valid = true;
 
-    constraint_valid && quotient_valid
-}
+    for (k, constraint) in constraints.iter().enumerate() {
+        let row_point = trace_domain.element(constraint.row);
+        let value = constraint.value;
+        for (i, &sample_point) in sampling_data.sample_points.iter().enumerate() {
+            let point = extended_eval_domain.element(sample_point);
+            let trace_value = sampling_data.sample_values[i][constraint.column];
+            // Quotient row is [Q, B_1, ..., B_m]
+            let quotient_value = sampling_data.quotient_values[i][k + 1];
+            let lhs = trace_value.subtract(value);
+            let rhs = quotient_value.multiply(point.subtract(row_point));
+            if lhs != rhs {
+                println!(
+                    "   ❌ Boundary {} (column {} row {} = {}) fails at point {}",
+                    k, constraint.column, constraint.row, constraint.value, sample_point
+                );
+                valid = false;
+            }
+        }
+    }
 
-/// Verify Merkle proofs for sample points (verifier only verifies, doesn't reconstruct)
-pub fn verify_merkle_proofs(proof: &StarkProof) -> bool {
-    println!("🌳 Verifying Merkle proofs for sample points...");
+    if valid {
+        println!(
+            "   ✅ All {} boundary constraints verified!",
+            constraints.len()
+        );
+    } else {
+        println!("   ❌ Some boundary constraints failed verification!");
+    }
 
+    valid
+}
+
+/// Verify Merkle proofs of opened rows against a commitment
+/// (verifier only verifies, doesn't reconstruct)
+pub fn verify_merkle_proofs<F: Field, H: Hasher>(
+    commitment: H::Digest,
+    points: &[usize],
+    rows: &[Vec<F>],
+    merkle_proofs: &[MerkleProof<H::Digest>],
+) -> bool {
     let mut valid = true;
 
-    for (i, (&sample_point, merkle_proof)) in proof
-        .sampling_data
-        .sample_points
+    for (i, ((&point, values), merkle_proof)) in points
         .iter()
-        .zip(proof.sampling_data.merkle_proofs.iter())
+        .zip(rows.iter())
+        .zip(merkle_proofs.iter())
         .enumerate()
     {
-        // Get the sample values provided by prover
-        let sample_values = &proof.sampling_data.sample_values[i];
-
-        if merkle_proof.is_empty() {
+        if merkle_proof.index != point {
             println!(
-                "   ❌ Sample {} (point {}): No Merkle proof provided",
-                i, sample_point
+                "   ❌ Sample {} (point {}): Merkle proof is for leaf {}",
+                i, point, merkle_proof.index
             );
             valid = false;
             continue;
         }
 
         // Compute row-leaf hash from all column values (must match prover logic)
-        let mut acc: i128 = 0;
-        for v in sample_values.iter() {
-            let h = v.hash();
-            acc = crate::merkle_tree::hash_two_inputs(acc, h);
-        }
-        // Tree was built from leaf hashes = accumulated_row_hash directly (no extra hash)
-        let leaf_hash = acc;
-        let mut current_hash = leaf_hash;
-
-        // Reconstruct root by following the proof path
-        for sibling in &merkle_proof[..merkle_proof.len() - 1] {
-            current_hash = crate::merkle_tree::hash_two_inputs(current_hash, *sibling);
-        }
+        let leaf_hash = H::hash_leaf(values);
 
         // Check if reconstructed root matches committed root
-        if current_hash == proof.trace_commitment {
+        if MerkleTree::<H>::verify(commitment, point, leaf_hash, merkle_proof) {
             println!(
                 "   ✅ Sample {} (point {}): Merkle proof verified",
-                i, sample_point
-            );
-            println!(
-                "      Values: {:?}",
-                sample_values.iter().map(|v| v.value).collect::<Vec<_>>()
+                i, point
             );
+            println!("      Values: {}", format_row(values));
         } else {
+            println!("   ❌ Sample {} (point {}): Merkle proof failed", i, point);
+            println!("      Expected root: {}", digest_hex::<H>(&commitment));
             println!(
-                "   ❌ Sample {} (point {}): Merkle proof failed",
-                i, sample_point
-            );
-            println!(
-                "      Expected root: {}, Got: {}",
-                proof.trace_commitment, current_hash
-            );
-            println!(
-                "      Leaf hash: {}, Values: {:?}",
-                leaf_hash,
-                sample_values.iter().map(|v| v.value).collect::<Vec<_>>()
+                "      Leaf hash: {}, Values: {}",
+                digest_hex::<H>(&leaf_hash),
+                format_row(values)
             );
             valid = false;
         }
     }
 
+    valid
+}
+
+/// Opened row values for the log, e.g. `[1, 2, 3]`
+fn format_row<F: Field>(values: &[F]) -> String {
+    let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+    format!("[{}]", values.join(", "))
+}
+
+/// Verify all openings: trace rows at x and g · x, and quotient rows at x
+fn verify_openings<F: Field, H: Hasher>(proof: &StarkProof<F, H>) -> bool {
+    println!("🌳 Verifying Merkle proofs for sample points...");
+
+    let sampling_data = &proof.sampling_data;
+    let blowup_factor = proof.options.blowup_factor;
+    let extended_size = proof.public_inputs.trace_length * blowup_factor;
+    let next_points: Vec<usize> = sampling_data
+        .sample_points
+        .iter()
+        .map(|&i| next_index(i, extended_size, blowup_factor))
+        .collect();
+
+    let trace_valid = verify_merkle_proofs::<F, H>(
+        proof.trace_commitment,
+        &sampling_data.sample_points,
+        &sampling_data.sample_values,
+        &sampling_data.merkle_proofs,
+    );
+    let next_valid = verify_merkle_proofs::<F, H>(
+        proof.trace_commitment,
+        &next_points,
+        &sampling_data.next_values,
+        &sampling_data.next_merkle_proofs,
+    );
+    let quotient_valid = verify_merkle_proofs::<F, H>(
+        proof.quotient_commitment,
+        &sampling_data.sample_points,
+        &sampling_data.quotient_values,
+        &sampling_data.quotient_merkle_proofs,
+    );
+
+    let valid = trace_valid && next_valid && quotient_valid;
     if valid {
         println!("   ✅ All Merkle proofs verified!");
     } else {
         println!("   ❌ Some Merkle proofs failed verification!");
     }
-
     valid
 }
 
-/// Generate random sample points (verifier's job)
-pub fn generate_sample_points(extended_trace_size: usize, num_samples: usize) -> Vec<usize> {
-    println!("🎲 Verifier generating random sample points...");
-
-    let mut sample_points = Vec::new();
-
-    // Simple PRNG for educational purposes
-    // In a real STARK, this would use Fiat-Shamir with the proof commitment
-    let mut rng_state = 12345u64; // Simple seed
-    for _ in 0..num_samples {
-        rng_state = rng_state.wrapping_mul(1103515245).wrapping_add(12345);
-        let sample_point = (rng_state as usize) % extended_trace_size;
-        sample_points.push(sample_point);
-        println!("   Generated sample point: {}", sample_point);
-    }
+/// Estimated soundness of a proof of `trace_length` rows with `options`, for the
+/// protocol as implemented: challenges in the base field F, commitments with `H`
+pub fn proof_security<F: Field, H: Hasher>(
+    options: &ProofOptions,
+    trace_length: usize,
+) -> SecurityLevel {
+    estimate_security::<F, H>(options, trace_length, 1)
+}
 
-    println!("   ✅ Generated {} random sample points", num_samples);
-    sample_points
+/// Name of the protocol, the first message of every transcript
+const PROTOCOL_LABEL: &[u8] = b"stark-from-zero";
+
+/// Start the protocol transcript. The field, the proof options and the public
+/// inputs go in first, so they influence every challenge of the proof.
+pub fn protocol_transcript<F: Field, H: Hasher>(
+    options: &ProofOptions,
+    public_inputs: &PublicInputs<F>,
+) -> Transcript<H> {
+    let mut t = Transcript::new(PROTOCOL_LABEL);
+    t.append_u64(b"field", F::CHARACTERISTIC);
+    options.absorb_into(&mut t);
+    public_inputs.absorb_into(&mut t);
+    t
 }
 
-/// Derive sample points using Fiat–Shamir from the commitment and leaf count
-pub fn derive_sample_points_from_commitment(
-    commitment: i128,
+/// Derive distinct, uniformly random sample points in 0..leaf_count from the
+/// transcript (at most `leaf_count` of them)
+pub fn derive_sample_points<H: Hasher>(
+    transcript: &mut Transcript<H>,
     leaf_count: usize,
     num_samples: usize,
 ) -> Vec<usize> {
     println!("🎲 Deriving sample points via Fiat–Shamir...");
-    let field = FiniteField::new(DEFAULT_FIELD_SIZE);
-    let mut t = Transcript::new();
-    t.absorb_i128(commitment);
-    t.absorb_i128(leaf_count as i128);
-
-    let mut points = Vec::with_capacity(num_samples);
-    for _ in 0..num_samples {
-        let c = t.challenge(field);
-        let idx = ((c.value % (leaf_count as i128)) + (leaf_count as i128)) % (leaf_count as i128);
-        points.push(idx as usize);
-    }
-    println!("   ✅ Derived {} sample points", num_samples);
+    transcript.append_u64(b"lde_size", leaf_count as u64);
+
+    let points = transcript.challenge_indices(
+        b"query_index",
+        leaf_count,
+        num_samples.min(leaf_count),
+        true,
+    );
+    println!("   ✅ Derived {} sample points", points.len());
     points
 }
 
-/// Derive FRI betas using Fiat–Shamir from the commitment
-pub fn derive_fri_betas_from_commitment(
-    commitment: i128,
-    num_rounds: usize,
-) -> Vec<FiniteFieldElement> {
-    println!("🧪 Deriving FRI betas via Fiat–Shamir...");
-    let field = FiniteField::new(DEFAULT_FIELD_SIZE);
-    let mut t = Transcript::new();
-    t.absorb_i128(commitment);
-
-    let mut betas = Vec::with_capacity(num_rounds);
-    for _ in 0..num_rounds {
-        let beta = t.challenge(field);
-        betas.push(beta);
+/// Append the trace commitment and derive the random coefficients combining
+/// the transition constraints
+pub fn derive_constraint_coefficients<F: Field, H: Hasher>(
+    transcript: &mut Transcript<H>,
+    trace_commitment: H::Digest,
+    num_constraints: usize,
+) -> Vec<F> {
+    println!("🧪 Deriving constraint coefficients via Fiat–Shamir...");
+    transcript.append_root(b"trace", trace_commitment);
+
+    let alphas = transcript.challenges(b"constraint_coefficients", num_constraints);
+    println!("   ✅ Derived {} coefficients", num_constraints);
+    alphas
+}
+
+/// Append the quotient commitment and derive the random coefficients combining
+/// the quotient columns, then the trace columns, into the FRI input
+pub fn derive_quotient_coefficients<F: Field, H: Hasher>(
+    transcript: &mut Transcript<H>,
+    quotient_commitment: H::Digest,
+    num_columns: usize,
+) -> Vec<F> {
+    transcript.append_root(b"quotient", quotient_commitment);
+    transcript.challenges(b"quotient_coefficients", num_columns)
+}
+
+/// Verify the FRI proof and that its first layer is the random combination of the
+/// opened quotient and trace rows at every sample point. This is what shows the
+/// trace columns are low degree, including columns no boundary constraint touches.
+fn verify_low_degree<A: Air, H: Hasher>(
+    air: &A,
+    proof: &StarkProof<A::Field, H>,
+    fri_coefficients: &[A::Field],
+    betas: &[A::Field],
+) -> bool {
+    println!("🧮 Verifying FRI proof...");
+
+    let sampling_data = &proof.sampling_data;
+    let options = &proof.options;
+    let domain = extended_domain(proof.public_inputs.trace_length, options.blowup_factor);
+    let domain_len = domain.size();
+    let Ok(final_layer_size) = options.fri_final_layer_size() else {
+        println!("   ❌ FRI final layer size overflows!");
+        return false;
+    };
+    if !verify_fri(
+        &proof.fri_proof,
+        betas,
+        &domain,
+        quotient_degree_bound(air),
+        final_layer_size,
+        &sampling_data.sample_points,
+    ) {
+        println!("   ❌ FRI layers are not consistent or not low degree!");
+        return false;
     }
-    println!("   ✅ Derived {} betas", num_rounds);
-    betas
+
+    let mut valid = true;
+    for (i, query) in proof.fri_proof.queries.iter().enumerate() {
+        let row: Vec<A::Field> = sampling_data.quotient_values[i]
+            .iter()
+            .chain(&sampling_data.sample_values[i])
+            .copied()
+            .collect();
+        let combined = combine_constraints(&row, fri_coefficients);
+        if query.first_layer_value(domain_len) != Some(combined) {
+            println!(
+                "   ❌ Sample {} (point {}): FRI layer does not match the quotient and trace rows",
+                i, query.index
+            );
+            valid = false;
+        }
+    }
+
+    if valid {
+        println!(
+            "   ✅ FRI verified! ({} layers, {} queries)",
+            proof.fri_proof.layer_commitments.len(),
+            proof.fri_proof.queries.len()
+        );
+    }
+    valid
 }
 
-/// Verify the entire STARK proof
-pub fn verify_proof(proof: &StarkProof) -> bool {
-    println!("🔍 Verifying STARK proof...");
+/// Check that the proof has the shape described by the AIR
+fn verify_shape<A: Air, H: Hasher>(air: &A, proof: &StarkProof<A::Field, H>) -> bool {
+    if proof.public_inputs != air.public_inputs() {
+        println!("   ❌ Proof public inputs do not match the claim being verified");
+        println!("      Proof: {:?}", proof.public_inputs);
+        println!("      Claim: {:?}", air.public_inputs());
+        return false;
+    }
+    if proof.public_inputs.trace_length < 2 {
+        println!("   ❌ Trace needs at least one transition");
+        return false;
+    }
+    if !proof.public_inputs.trace_length.is_power_of_two() {
+        println!("   ❌ Trace length must be a power of two");
+        return false;
+    }
+    if let Err(e) = proof.options.validate_for::<A::Field>(
+        proof.public_inputs.trace_length,
+        air.max_constraint_degree(),
+    ) {
+        println!("   ❌ Unusable proof options: {}", e);
+        return false;
+    }
 
-    // Check if we have sample data
-    if proof.sampling_data.sample_points.is_empty() {
+    let sampling_data = &proof.sampling_data;
+    let num_samples = sampling_data.sample_points.len();
+    if num_samples == 0 {
         println!("   ❌ No sample data provided by prover!");
         return false;
     }
+    if [
+        sampling_data.sample_values.len(),
+        sampling_data.next_values.len(),
+        sampling_data.quotient_values.len(),
+        sampling_data.merkle_proofs.len(),
+        sampling_data.next_merkle_proofs.len(),
+        sampling_data.quotient_merkle_proofs.len(),
+    ]
+    .iter()
+    .any(|&len| len != num_samples)
+    {
+        println!("   ❌ Sampling data has inconsistent lengths");
+        return false;
+    }
+    if sampling_data
+        .sample_values
+        .iter()
+        .chain(sampling_data.next_values.iter())
+        .any(|row| row.len() != air.trace_width())
+    {
+        println!(
+            "   ❌ Sampled rows do not have {} columns",
+            air.trace_width()
+        );
+        return false;
+    }
+    let quotient_width = 1 + proof.public_inputs.boundary_constraints.len();
+    if sampling_data
+        .quotient_values
+        .iter()
+        .any(|row| row.len() != quotient_width)
+    {
+        println!("   ❌ Quotient rows do not have {} columns", quotient_width);
+        return false;
+    }
+    true
+}
+
+/// Verify the entire STARK proof against the AIR
+pub fn verify<A: Air, H: Hasher>(air: &A, proof: &StarkProof<A::Field, H>) -> bool {
+    println!("🔍 Verifying STARK proof...");
+
+    if !verify_shape(air, proof) {
+        println!("   ❌ STARK proof is INVALID!");
+        return false;
+    }
+
+    // Recompute the Fiat–Shamir challenges from the commitments
+    let public_inputs = &proof.public_inputs;
+    let options = &proof.options;
+    // verify_shape checked that this product fits
+    let extended_size = public_inputs.trace_length * options.blowup_factor;
+    let mut transcript = protocol_transcript(options, public_inputs);
+    let alphas = derive_constraint_coefficients(
+        &mut transcript,
+        proof.trace_commitment,
+        air.num_transition_constraints(),
+    );
+    let fri_coefficients = derive_quotient_coefficients(
+        &mut transcript,
+        proof.quotient_commitment,
+        1 + public_inputs.boundary_constraints.len() + air.trace_width(),
+    );
+    let betas = derive_betas(&proof.fri_proof, &mut transcript);
+    if !transcript.verify_pow(proof.pow_nonce, options.grinding_bits) {
+        println!(
+            "   ❌ Proof-of-work nonce does not have {} leading zero bits!",
+            options.grinding_bits
+        );
+        println!("   ❌ STARK proof is INVALID!");
+        return false;
+    }
+    let sample_points = derive_sample_points(&mut transcript, extended_size, options.num_queries);
+    if proof.sampling_data.sample_points != sample_points {
+        println!("   ❌ Sample points do not match the Fiat–Shamir challenges!");
+        println!("   ❌ STARK proof is INVALID!");
+        return false;
+    }
 
-    // Step 1: Verify Merkle proofs for sample points
-    let merkle_valid = verify_merkle_proofs(proof);
+    // Step 1: Verify Merkle proofs for the opened rows
+    let merkle_valid = verify_openings(proof);
 
-    // Step 2: Verify constraint polynomial at sample points
-    let constraint_valid = verify_random_sampling(proof);
+    // Step 2: Verify transition constraints at sample points
+    let constraint_valid = verify_transition_constraints(air, proof, &alphas);
 
-    // Both verifications must pass
-    let is_valid = merkle_valid && constraint_valid;
+    // Step 3: Verify boundary constraints (public inputs) at sample points
+    let boundary_valid = verify_boundary_constraints(proof);
+
+    // Step 4: Verify the quotients are low degree with FRI
+    let fri_valid = verify_low_degree(air, proof, &fri_coefficients, &betas);
+
+    // All verifications must pass
+    let is_valid = merkle_valid && constraint_valid && boundary_valid && fri_valid;
 
     if is_valid {
         println!("   ✅ STARK proof is VALID!");
@@ -344,7 +569,28 @@ pub fn verify_proof(proof: &StarkProof) -> bool {
         if !constraint_valid {
             println!("   ❌ Constraint verification failed!");
         }
+        if !boundary_valid {
+            println!("   ❌ Boundary constraint verification failed!");
+        }
+        if !fri_valid {
+            println!("   ❌ FRI verification failed!");
+        }
     }
 
     is_valid
 }
+
+/// Verify the proof, but refuse it outright if its options and its hash `H` give
+/// less than `target_bits` of conjectured security
+pub fn verify_with_security<A: Air, H: Hasher>(
+    air: &A,
+    proof: &StarkProof<A::Field, H>,
+    target_bits: u32,
+) -> bool {
+    let security = proof_security::<A::Field, H>(&proof.options, proof.public_inputs.trace_length);
+    if let Err(e) = security.check(target_bits) {
+        println!("🔍 Refusing STARK proof: {}", e);
+        return false;
+    }
+    verify(air, proof)
+}